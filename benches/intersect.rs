@@ -0,0 +1,44 @@
+use std::{hint::black_box, time::Instant};
+
+use quickmatch::QuickMatch;
+
+/// Multi-word exact queries over a synthetic corpus, the case the sorted
+/// id lists are intersected for: `cargo bench --bench intersect`.
+fn main() {
+    // Zipf-like word draws from a fixed generator, so common words have
+    // long postings and rare ones short
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move |n: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let r = state % n + 1;
+        (n / r).min(n - 1)
+    };
+    let items: Vec<String> = (0..200_000)
+        .map(|i| {
+            let words: Vec<String> = (0..4).map(|_| format!("w{}", next(5_000))).collect();
+            format!("{} item{i}", words.join(" "))
+        })
+        .collect();
+    let items: Vec<&str> = items.iter().map(String::as_str).collect();
+    let qm = QuickMatch::new(&items);
+
+    let queries: Vec<String> = (0..2_000)
+        .map(|_| format!("w{} w{}", next(50), next(500)))
+        .collect();
+
+    for _ in 0..3 {
+        let start = Instant::now();
+        let mut results = 0;
+        for query in &queries {
+            results += black_box(qm.matches(black_box(query))).len();
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "{} queries in {elapsed:?} ({:?} per query, {results} results)",
+            queries.len(),
+            elapsed / queries.len() as u32
+        );
+    }
+}
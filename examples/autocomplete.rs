@@ -25,9 +25,7 @@ fn main() {
     .map(|s| s.to_lowercase())
     .collect::<Vec<_>>();
 
-    let products_ref = products.iter().map(|s| s.as_str()).collect::<Vec<_>>();
-
-    let matcher = QuickMatch::new(&products_ref);
+    let matcher = QuickMatch::from_items(&products);
 
     println!("Type to search (press Ctrl+C to exit):");
     println!("Try: 'apple', 'pro', 'laptop', 'headphones', etc.\n");
@@ -0,0 +1,26 @@
+use std::{env, fs, net::TcpListener};
+
+use quickmatch::{OwnedQuickMatch, http};
+
+/// Typeahead service over the first column of a CSV file:
+/// `cargo run --example serve --features http -- items.csv 127.0.0.1:8080`,
+/// then `curl 'localhost:8080/search?q=hash+rate&limit=5'`.
+fn main() -> std::io::Result<()> {
+    let mut args = env::args().skip(1);
+    let path = args.next().expect("usage: serve <file.csv> [addr]");
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+
+    let content = fs::read_to_string(path)?;
+    let qm: OwnedQuickMatch = content
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .map(|item| item.trim().to_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect();
+
+    println!(
+        "serving {} items on http://{addr}/search?q=",
+        qm.matcher().len()
+    );
+    http::serve(qm.matcher(), TcpListener::bind(addr)?)
+}
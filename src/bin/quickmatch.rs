@@ -0,0 +1,107 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    process::ExitCode,
+};
+
+use quickmatch::OwnedQuickMatch;
+
+const USAGE: &str = "usage:
+  quickmatch build <input|-> -o <index.qm>   one item per line; - reads stdin
+  quickmatch query <index.qm> <query> [--limit <n>]";
+
+/// Prebuilds an index from a word list and queries it, for pipelines that
+/// don't link the crate. Items are lowercased on the way in, as the
+/// matcher expects.
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("build") => build(&args[1..]),
+        Some("query") => query(&args[1..]),
+        _ => Err(usage()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("quickmatch: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn build(args: &[String]) -> io::Result<()> {
+    let (input, output) = match args {
+        [input, flag, output] if flag == "-o" => (input, output),
+        [flag, output, input] if flag == "-o" => (input, output),
+        _ => return Err(usage()),
+    };
+    let reader: Box<dyn BufRead> = if input == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(input)?))
+    };
+    let qm = OwnedQuickMatch::from_lines(Lowercased {
+        inner: reader,
+        line: String::new(),
+        at: 0,
+    })?;
+    qm.save(output)?;
+    eprintln!("{} items written to {output}", qm.matcher().len());
+    Ok(())
+}
+
+fn query(args: &[String]) -> io::Result<()> {
+    let (index, query, limit) = match args {
+        [index, query] => (index, query, None),
+        [index, query, flag, n] if flag == "--limit" => {
+            let n = n.parse::<usize>().map_err(|_| usage())?;
+            (index, query, Some(n))
+        }
+        _ => return Err(usage()),
+    };
+    let qm = OwnedQuickMatch::load(index, Default::default())?;
+    let mut results = qm.matches(query);
+    if let Some(limit) = limit {
+        results.truncate(limit);
+    }
+    for item in results {
+        println!("{item}");
+    }
+    Ok(())
+}
+
+/// Lowercases a reader one line at a time, so the input still streams.
+struct Lowercased<R> {
+    inner: R,
+    line: String,
+    at: usize,
+}
+
+impl<R: BufRead> Read for Lowercased<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Lowercased<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.at == self.line.len() {
+            let mut line = String::new();
+            self.inner.read_line(&mut line)?;
+            self.line = line.to_lowercase();
+            self.at = 0;
+        }
+        Ok(&self.line.as_bytes()[self.at..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.at += amt;
+    }
+}
+
+fn usage() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, USAGE)
+}
@@ -0,0 +1,75 @@
+use crate::distance::edit_distance;
+
+/// Burkhard-Keller tree of words under edit distance: each child hangs off
+/// its parent by their distance, so by the triangle inequality a search
+/// within `max` of a word only descends into children within `max` of the
+/// distance to their parent.
+#[derive(Clone, Default)]
+pub(crate) struct BkTree {
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone)]
+struct Node {
+    word: String,
+    children: Vec<(usize, usize)>,
+}
+
+impl BkTree {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        for node in &mut self.nodes {
+            node.children.shrink_to_fit();
+        }
+        self.nodes.shrink_to_fit();
+    }
+
+    pub(crate) fn insert(&mut self, word: &str) {
+        let new = self.nodes.len();
+        let mut at = 0;
+        while at < self.nodes.len() {
+            let distance = edit_distance(&self.nodes[at].word, word);
+            if distance == 0 {
+                return;
+            }
+            match self.nodes[at].children.iter().find(|(d, _)| *d == distance) {
+                Some(&(_, child)) => at = child,
+                None => {
+                    self.nodes[at].children.push((distance, new));
+                    break;
+                }
+            }
+        }
+        self.nodes.push(Node {
+            word: word.to_string(),
+            children: vec![],
+        });
+    }
+
+    /// Words within `max` edits of `word`, with their distances.
+    pub(crate) fn find(&self, word: &str, max: usize) -> Vec<(&str, usize)> {
+        let mut found = vec![];
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(at) = stack.pop() {
+            let node = &self.nodes[at];
+            let distance = edit_distance(&node.word, word);
+            if distance <= max {
+                found.push((node.word.as_str(), distance));
+            }
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|(d, _)| d.abs_diff(distance) <= max)
+                    .map(|&(_, child)| child),
+            );
+        }
+        found
+    }
+}
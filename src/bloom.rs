@@ -0,0 +1,52 @@
+/// Bloom filter over gram keys, blocked so a lookup reads one `u64`: a key
+/// sets 3 bits of a single block. Grows by doubling, re-adding every key,
+/// once past 16 bits per key, which keeps false positives near 1%.
+#[derive(Clone, Default)]
+pub(crate) struct GramFilter {
+    blocks: Vec<u64>,
+    len: usize,
+}
+
+impl GramFilter {
+    pub(crate) fn with_keys(keys: impl ExactSizeIterator<Item = u64>) -> Self {
+        let mut filter = Self {
+            blocks: vec![0; (keys.len() / 4 + 1).next_power_of_two()],
+            len: 0,
+        };
+        for key in keys {
+            filter.insert(key);
+        }
+        filter
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.len >= self.blocks.len() * 4
+    }
+
+    pub(crate) fn insert(&mut self, key: u64) {
+        let (block, bits) = self.locate(key);
+        self.blocks[block] |= bits;
+        self.len += 1;
+    }
+
+    /// False only for keys never inserted; true when nothing is built.
+    pub(crate) fn may_contain(&self, key: u64) -> bool {
+        if self.blocks.is_empty() {
+            return true;
+        }
+        let (block, bits) = self.locate(key);
+        self.blocks[block] & bits == bits
+    }
+
+    fn locate(&self, key: u64) -> (usize, u64) {
+        // SplitMix64's finalizer, since gram keys are packed chars whose
+        // low bits barely vary
+        let mut h = key;
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+        h ^= h >> 31;
+        let block = (h >> 32) as usize & (self.blocks.len() - 1);
+        let bits = (1 << (h & 63)) | (1 << ((h >> 6) & 63)) | (1 << ((h >> 12) & 63));
+        (block, bits)
+    }
+}
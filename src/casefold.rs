@@ -0,0 +1,32 @@
+use std::borrow::Cow;
+
+use crate::CaseFolding;
+
+/// The text with its letters folded to lowercase under `folding`, borrowed
+/// when nothing changes. ASCII folding leaves non-ASCII chars alone.
+///
+/// Unicode folding lowercases every cased letter and applies the full
+/// foldings that make spellings of a word match: "ß" and "ẞ" become "ss",
+/// and final sigma "ς" becomes "σ", so "ΟΔΟΣ" and "οδος" match. Turkish
+/// folding maps dotted "İ" to "i" and dotless "I" to "ı".
+pub(crate) fn fold(text: &str, folding: CaseFolding) -> Cow<'_, str> {
+    let keeps = |c: char| match folding {
+        CaseFolding::Ascii => !c.is_ascii_uppercase(),
+        CaseFolding::Unicode | CaseFolding::Turkish => !c.is_uppercase() && !matches!(c, 'ß' | 'ς'),
+    };
+    if text.chars().all(keeps) {
+        return Cow::Borrowed(text);
+    }
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match (folding, c) {
+            (CaseFolding::Ascii, c) => folded.push(c.to_ascii_lowercase()),
+            (CaseFolding::Turkish, 'I') => folded.push('ı'),
+            (CaseFolding::Turkish, 'İ') => folded.push('i'),
+            (_, 'ß' | 'ẞ') => folded.push_str("ss"),
+            (_, 'ς') => folded.push('σ'),
+            (_, c) => folded.extend(c.to_lowercase()),
+        }
+    }
+    Cow::Owned(folded)
+}
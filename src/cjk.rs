@@ -0,0 +1,78 @@
+/// Han, kana and Hangul: scripts written without spaces between words.
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1100}'..='\u{11FF}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3130}'..='\u{318F}'
+            | '\u{31F0}'..='\u{31FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF66}'..='\u{FF9F}'
+            | '\u{20000}'..='\u{2A6DF}'
+    )
+}
+
+/// Splits a word into its CJK and other runs, in order.
+fn runs(word: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut rest = word;
+    std::iter::from_fn(move || {
+        let cjk = is_cjk(rest.chars().next()?);
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| is_cjk(c) != cjk)
+            .map_or(rest.len(), |(i, _)| i);
+        let (run, tail) = rest.split_at(end);
+        rest = tail;
+        Some((run, cjk))
+    })
+}
+
+/// Overlapping two-char windows of a CJK run, or the run itself when it is
+/// a single char.
+fn bigrams(run: &str) -> Vec<&str> {
+    let starts: Vec<usize> = run.char_indices().map(|(i, _)| i).collect();
+    let ends = starts.iter().skip(2).copied().chain([run.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| &run[start..end])
+        .collect()
+}
+
+/// Extra word-index keys for a word holding CJK text: every char and
+/// bigram of its CJK runs, and every prefix of its other runs so they
+/// are found on their own. Nothing for a word without CJK.
+pub(crate) fn index_terms(word: &str) -> Vec<&str> {
+    if !word.chars().any(is_cjk) {
+        return vec![];
+    }
+    let mut terms = vec![];
+    for (run, cjk) in runs(word) {
+        if cjk {
+            terms.extend(run.char_indices().map(|(i, c)| &run[i..i + c.len_utf8()]));
+            terms.extend(
+                bigrams(run)
+                    .into_iter()
+                    .filter(|gram| gram.chars().nth(1).is_some()),
+            );
+        } else {
+            terms.extend(
+                (1..=run.len())
+                    .filter(|&len| run.is_char_boundary(len))
+                    .map(|len| &run[..len]),
+            );
+        }
+    }
+    terms
+}
+
+/// A query word as index terms: other runs stay whole, CJK runs become
+/// their bigrams, so every bigram of the query must be in the item.
+pub(crate) fn query_terms(word: &str) -> Vec<&str> {
+    runs(word)
+        .flat_map(|(run, cjk)| if cjk { bigrams(run) } else { vec![run] })
+        .collect()
+}
@@ -1,13 +1,45 @@
+use std::{borrow::Cow, cmp::Ordering, fmt::Display, sync::Arc, time::Duration};
+
+use crate::{
+    ConfigError, KeyboardLayout, Match, Metrics, SlowQuery, metrics::OnSlowQuery, punctuation,
+};
+
 const DEFAULT_SEPARATORS: &[char] = &['_', '-', ' ', ':', '/'];
 const DEFAULT_TRIGRAM_BUDGET: usize = 6;
 const DEFAULT_LIMIT: usize = 100;
+const DEFAULT_PARALLEL_THRESHOLD: usize = 10_000;
 const DEFAULT_MIN_SCORE: usize = 2;
+const DEFAULT_MIN_HIT_RATIO: f32 = 0.5;
+const DEFAULT_MIN_RELEVANCE: f32 = 0.0;
+const CODE_SEPARATORS: &[char] = &['_', '-', ' ', ':', '/', '.'];
+const NAME_SEPARATORS: &[char] = &[' ', '-', '\'', '.', ','];
+/// Items sampled by `tuned_for`.
+const TUNING_SAMPLE: usize = 1000;
 
+#[derive(Clone)]
 pub struct QuickMatchConfig {
-    /// Separators used to split words.
+    /// Which ways of matching a query word are tried; see `MatchMode`.
+    ///
+    /// Default: MatchMode::Hybrid
+    match_mode: MatchMode,
+    /// Separators used to split words. Items are split once, under the
+    /// matcher's own config; a config passed to `matches_with` and the
+    /// like only changes how the query is split.
     ///
     /// Default: ['_', '-', ' ', ':', '/']
     separators: &'static [char],
+    /// Chars that split words on top of `separators`, e.g. every char
+    /// that isn't alphanumeric, without listing them all. Spaces then
+    /// split words too.
+    ///
+    /// Default: None
+    split_when: Option<fn(char) -> bool>,
+    /// Whether all Unicode whitespace and punctuation split words, so tabs,
+    /// no-break spaces, dashes and CJK punctuation don't end up inside
+    /// them.
+    ///
+    /// Default: false
+    unicode_separators: bool,
     /// Maximum number of results to return.
     ///
     /// Default: 100
@@ -23,21 +55,494 @@ pub struct QuickMatchConfig {
     /// - High (9-15): Slower, more accurate fuzzy matching
     /// - Max: 20
     trigram_budget: usize,
+    /// Grant the full trigram budget to each unknown word instead of sharing
+    /// it, so several misspelled words don't starve each other.
+    ///
+    /// Default: false
+    per_word_budget: bool,
+    /// Probe each typo'd word's trigrams rarest first (by the number of
+    /// items holding them) instead of from both ends toward the middle,
+    /// skipping trigrams no item has: rare ones narrow the candidates
+    /// fastest and score most precisely.
+    ///
+    /// Default: false
+    rarest_first: bool,
+    /// Let the matcher pick the trigram budget from its corpus instead (see
+    /// `QuickMatch::auto_trigram_budget`), overriding `trigram_budget`.
+    ///
+    /// Default: false
+    auto_trigram_budget: bool,
+    /// Skip typo matching when the items holding the known query words
+    /// already fill `limit`, ranking just those; on common queries this
+    /// saves the most expensive phase.
+    ///
+    /// Default: false
+    pool_shortcut: bool,
+    /// Before typo matching a word that isn't indexed, look for item words
+    /// within this many edits of it (one per 3 chars of the word at most),
+    /// walking the sorted vocabulary with a Levenshtein automaton.
+    ///
+    /// Default: 0 (off)
+    /// - Max: 2
+    max_edits: usize,
+    /// Before typo matching a word that isn't indexed, look up this many of
+    /// its one-edit variants in the word index: deletions, swaps of
+    /// neighbors, substitutions, then insertions. For short words, faster
+    /// and more precise than trigrams; about 75 per char of the word covers
+    /// every variant.
+    ///
+    /// Default: 0 (off)
+    edit_expansions: usize,
+    /// Keep a BK-tree of the item words, and when typo matching finds
+    /// nothing, retry the query with each unmatched word replaced by the
+    /// nearest of them. Also speeds up `QuickMatch::nearest_words`.
+    ///
+    /// Default: false
+    bk_tree: bool,
+    /// Also keep each word's items as a sorted list of ids, and intersect
+    /// the postings of exact query words by galloping through those lists
+    /// instead of probing hash sets. Faster on large corpora, for the
+    /// memory of a second copy of the word postings.
+    ///
+    /// Default: false
+    sorted_postings: bool,
+    /// Keep a Bloom filter of the indexed trigrams and check it before
+    /// each trigram lookup, so typo matching garbage input mostly skips
+    /// the hash map.
+    ///
+    /// Default: false
+    trigram_filter: bool,
+    /// Build only the word index up front, and the trigram index on the
+    /// first query needing it (a typo, `min_coverage`, the auto budget) or
+    /// on `QuickMatch::prepare_trigrams`, e.g. from a background thread.
+    /// For matchers that may only ever see exact words.
+    ///
+    /// Default: false
+    lazy_trigrams: bool,
+    /// Build no trigram index at all, for the memory: queries match exact
+    /// words (and prefixes) only, as with a trigram budget of 0 whatever
+    /// the query config says.
+    ///
+    /// Default: false
+    words_only: bool,
     /// Minimum trigram score required for fuzzy matches.
     /// Higher values require more trigram overlap, reducing noise.
     ///
     /// Default: 2
     /// - Min: 1
     min_score: usize,
+    /// Share of the probed trigrams (those some item has) a typo match
+    /// must hold, with `min_score` as the absolute floor. Items holding
+    /// every known query word start one trigram ahead, so for them the bar
+    /// is one trigram lower.
+    ///
+    /// Default: 0.5
+    /// - Min: 0.0 (any trigram will do, down to `min_score`)
+    /// - Max: 1.0 (every probed trigram)
+    min_hit_ratio: f32,
+    /// How typo matches, and with `Scoring::Cosine` exact matches too, are
+    /// scored against each other; see `Scoring`.
+    /// `min_score` still decides which items count as typo matches.
+    ///
+    /// Default: Scoring::Hits
+    scoring: Scoring,
+    /// Record where each trigram sits in its word at build time, and give
+    /// typo matches full credit only for trigrams found near the same spot
+    /// in the query word (half credit elsewhere). Sharpens ranking for
+    /// transposition-heavy typos at the cost of a larger index.
+    ///
+    /// Default: false
+    positional_trigrams: bool,
+    /// Also index skip-grams (chars 1, 2 and 4 of every 4-char window), and
+    /// probe query trigrams among them and query skip-grams among the
+    /// trigrams, so a swap of adjacent chars ("thier") still shares grams
+    /// with the target ("their"). Roughly doubles the gram index.
+    ///
+    /// Default: false
+    skip_grams: bool,
+    /// Before trigram fuzzing, try unknown query words with one char swapped
+    /// for a neighboring key on this layout ("qpple" → "apple").
+    ///
+    /// Default: None (off)
+    keyboard_layout: Option<KeyboardLayout>,
+    /// Index the initials of each multi-word item, so a query word like
+    /// "xsx" that isn't a known word finds "xbox series x".
+    ///
+    /// Default: false
+    acronyms: bool,
+    /// Index a sound-alike key per word, and once the typo fallbacks find
+    /// nothing for a query word, try the items with a word that sounds like
+    /// it ("kathryn" → "catherine").
+    ///
+    /// Default: false
+    #[cfg(feature = "phonetic")]
+    phonetic: bool,
+    /// Index each distinct item text once, skipping later items with the
+    /// same text at another address (see `QuickMatch::duplicates`).
+    ///
+    /// Default: false
+    dedup: bool,
+    /// Window trigrams and skip-grams over grapheme clusters instead of
+    /// chars, so combining marks, emoji sequences and Indic vowel signs
+    /// stay with the char they belong to. Queries keep their non-ASCII
+    /// text, and typo matching probes any word in the same units.
+    ///
+    /// Default: false
+    #[cfg(feature = "unicode")]
+    graphemes: bool,
+    /// Window item trigrams over bytes instead of chars, skipping the
+    /// per-word char buffer while building. Grams are the same for ASCII
+    /// words, so this is for ASCII corpora; other words get grams of their
+    /// UTF-8 bytes, which typo matching, probing only ASCII query words,
+    /// never looks up. Takes precedence over `graphemes`.
+    ///
+    /// Default: false
+    byte_trigrams: bool,
+    /// Index Chinese, Japanese and Korean text, which has no separators,
+    /// by its chars and overlapping char pairs, and keep CJK chars in
+    /// queries, where each run must match all of its pairs.
+    ///
+    /// Default: false
+    cjk_bigrams: bool,
+    /// Fold compatibility chars in items and queries to their plain
+    /// equivalents, as Unicode's NFKC does, so full-width forms and
+    /// ligatures ("１５", "ﬁle") match plain text ("15", "file").
+    ///
+    /// Default: false
+    nfkc: bool,
+    /// Let query numbers from 0 to 20 and ordinals from 1st to 20th also
+    /// find their other spelling ("5" ↔ "five", "3rd" ↔ "third").
+    ///
+    /// Default: false
+    number_words: bool,
+    /// Join numbers to the common units after them, in items and queries,
+    /// so "16gb", "16 gb" and "16GB" all match, as do "4k" and "4 k".
+    ///
+    /// Default: false
+    units: bool,
+    /// What becomes of apostrophes and hyphens inside words, in items and
+    /// queries alike; see `Joiners`. Elsewhere they become spaces.
+    ///
+    /// Default: None (apostrophes stay in words, hyphens separate them)
+    joiners: Option<Joiners>,
+    /// What becomes of emoji and symbols in items and queries; see
+    /// `Symbols`.
+    ///
+    /// Default: Symbols::Keep
+    symbols: Symbols,
+    /// How letter case is folded; see `CaseFolding`.
+    ///
+    /// Default: CaseFolding::Ascii
+    case_folding: CaseFolding,
+    /// Treat each boundary between letters and digits as a word break, in
+    /// items and queries, so "15pro" finds "15 pro" and "xps 13" finds
+    /// "xps13".
+    ///
+    /// Default: false
+    split_digits: bool,
+    /// Treat items as file paths: split on `/`, `\` and `.` on top of the
+    /// separators, so extensions are words of their own, and among items
+    /// matching as many query words, rank first those whose file name
+    /// holds one.
+    ///
+    /// Default: false
+    paths: bool,
+    /// Split emails and URLs into their parts: on `@`, `.`, `/`, `:`, `?`,
+    /// `=`, `&`, `#` and `+` on top of the separators, so the local part,
+    /// each domain label and each path segment is a word, and "gmail
+    /// smith" finds "john.smith@gmail.com".
+    ///
+    /// Default: false
+    web_addresses: bool,
+    /// Record the word sequence of each item, so queries can quote phrases
+    /// (`"galaxy tab" case`) whose words must appear exactly and next to
+    /// each other, in order. Costs a word id per indexed word.
+    ///
+    /// Default: false
+    phrases: bool,
+    /// Minimum normalized score (see `Match::score`) a result needs to be
+    /// returned, e.g. 0.4 to hide weak fuzzy matches.
+    ///
+    /// Default: 0.0 (keep everything)
+    /// - Range: 0.0 to 1.0
+    min_relevance: f32,
+    /// Minimum share of query words an item must satisfy, exactly or
+    /// fuzzily (at least half of the word's trigrams), to be returned, e.g.
+    /// 0.6 so a 5-word query needs 3 of them.
+    ///
+    /// Default: 0.0 (keep everything)
+    /// - Range: 0.0 to 1.0
+    min_coverage: f32,
+    /// Match items holding any of the query words instead of all of them
+    /// ("sony samsung" finds both brands), ranked by how many words each
+    /// item covers. Typo'd words then search the whole index, not just the
+    /// items matching the known words.
+    ///
+    /// Default: false (all known words must match)
+    any_word: bool,
+    /// Index whole words only, with a sorted word list, and expand each
+    /// query word to the indexed words it prefixes ("log" → "logitech"),
+    /// taking at most this many in sorted order. Shrinks the word index
+    /// several times over, at some cost per query word.
+    ///
+    /// Default: None (every prefix of every word is indexed up front)
+    /// - Some(usize::MAX): expand without a cap
+    prefix_expansion: Option<usize>,
+    /// Also match each query word of 3 chars or more to the indexed words
+    /// holding it past their start ("book" → "macbook"), like SQL's
+    /// `LIKE '%book%'`, taking at most this many in sorted order. Scans
+    /// the distinct item words for each query word.
+    ///
+    /// Default: None (off)
+    infix_expansion: Option<usize>,
+    /// How much an item's weight (see `QuickMatch::set_weight`) adds to its
+    /// relevance when ranking: it sorts by relevance + blend × weight.
+    ///
+    /// Default: 0.1
+    /// - Range: 0.0 and up
+    weight_blend: f32,
+    /// Keep only the best-ranked of results that are near-duplicates:
+    /// the same words in any order, or trigram sets overlapping (Jaccard)
+    /// at least this much, e.g. 0.5 so storage variants of one phone
+    /// don't fill the page.
+    ///
+    /// Default: None (no collapsing)
+    /// - Range: 0.0 to 1.0
+    collapse: Option<f32>,
+    /// Longest query accepted, measured after normalization (trim,
+    /// lowercase, non-ASCII dropped). Longer queries return no results,
+    /// and so do queries with more words than any item holds, by a margin,
+    /// whatever this is set to.
+    ///
+    /// Default: None (the longest indexed item plus a small margin)
+    /// - Some(n): accept up to n chars
+    /// - Some(usize::MAX): no length limit
+    max_query_len: Option<usize>,
+    /// Wall-clock budget per query. Once spent, no more trigrams are probed
+    /// and no more candidates are ranked; the best results found so far are
+    /// returned (flagged by `Matches::truncated`).
+    ///
+    /// Default: None (no limit)
+    time_budget: Option<Duration>,
+    /// Threads a batch of queries is spread across, 0 for one per available
+    /// core. 1 keeps everything on the calling thread, which suits async
+    /// servers running their own pool.
+    ///
+    /// Default: 1
+    parallelism: usize,
+    /// Fewest items for `parallelism` to apply; smaller matchers answer
+    /// batches on the calling thread, where spawning would cost more than
+    /// it saves.
+    ///
+    /// Default: 10000
+    parallel_threshold: usize,
+    /// Most candidates a query scores and ranks; past it, typo'd words
+    /// add no new items and ranking stops taking them, flagging the
+    /// results as truncated. Bounds the work of trigrams shared by huge
+    /// numbers of items.
+    ///
+    /// Default: None (no limit)
+    max_candidates: Option<usize>,
+    /// Receives counts of queries, candidates and trigram probes.
+    ///
+    /// Default: None
+    metrics: Option<Arc<dyn Metrics>>,
+    /// Called with the normalized query and its phase timings whenever a
+    /// query takes at least this long, to find inputs that need fixing.
+    ///
+    /// Default: None
+    slow_query: Option<(Duration, Arc<OnSlowQuery>)>,
+    /// Replaces the built-in cleanup (trim, NFKC folding, dropping
+    /// non-ASCII, lowercasing) of queries, and is applied to items too
+    /// before they are split into words, e.g. to strip SKU punctuation.
+    ///
+    /// Default: None (built-in cleanup for queries, items as given)
+    normalizer: Option<Arc<Normalizer>>,
+    /// Orders results that match the same number of query words, in place
+    /// of trigram score, match position and length, e.g. newest first.
+    /// Remaining ties are still broken by `tiebreak`.
+    ///
+    /// Default: None (built-in order)
+    order: Option<Arc<Order>>,
+    /// Orders the results the ranking leaves tied; see `Tiebreak`.
+    ///
+    /// Default: Tiebreak::Text
+    tiebreak: Tiebreak,
+    /// Return the results in the order the items were given instead of by
+    /// rank, for callers that only filter. Which items make the `limit`
+    /// is still decided by rank.
+    ///
+    /// Default: false
+    input_order: bool,
+    /// Values the setters had to bring into range, reported by `try_build`.
+    clamped: Vec<ConfigError>,
+}
+
+/// What becomes of an apostrophe or hyphen between two letters or digits,
+/// as in "o'brien" or "x-ray". Curly apostrophes and Unicode hyphens count
+/// too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Joiners {
+    /// Part of the word: "o'brien" and "o’brien" are one word.
+    Keep,
+    /// Word breaks: "o'brien" is "o" and "brien".
+    Split,
+    /// Dropped: "l'oreal", "l’oreal" and "loreal" are all "loreal".
+    Merge,
+}
+
+/// What becomes of emoji, pictographs, dashes, arrows and similar symbols,
+/// which otherwise stick to the words next to them and fill their grams.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Symbols {
+    /// Left as they are.
+    #[default]
+    Keep,
+    /// Dropped, so "🔥 Best Deal – iPhone 15" indexes as "Best Deal
+    /// iPhone 15".
+    Strip,
+    /// Set off by spaces as words of their own, so they stay searchable
+    /// without touching the words around them. Needs `' '` among the
+    /// separators.
+    Isolate,
+}
+
+/// How items and queries are lowercased.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseFolding {
+    /// ASCII letters only: queries are lowercased and lose their other
+    /// non-ASCII chars (but CJK ones with `cjk_bigrams`), and items are
+    /// taken as given.
+    #[default]
+    Ascii,
+    /// Every cased letter, in items and queries, with "ß" as "ss" and
+    /// final sigma as plain sigma. Queries keep their non-ASCII chars.
+    Unicode,
+    /// As `Unicode`, but with Turkish and Azerbaijani dotted and dotless
+    /// i: "I" folds to "ı" and "İ" to "i".
+    Turkish,
+}
+
+/// Which ways of matching a query word are tried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Whole item words only.
+    ExactWords,
+    /// Item words the query word starts (or holds, with
+    /// `infix_expansion`), and joined words.
+    Prefix,
+    /// Trigram typo matching only, for query words of 3 chars or more;
+    /// shorter words match nothing.
+    Fuzzy,
+    /// Prefixes, then for words they miss the configured phonetic,
+    /// acronym, keyboard and edit-distance lookups, then trigrams.
+    #[default]
+    Hybrid,
+}
+
+impl MatchMode {
+    /// Whether typo matching runs at all.
+    pub(crate) fn typos(self) -> bool {
+        matches!(self, Self::Fuzzy | Self::Hybrid)
+    }
+}
+
+/// Fuzzy score of a typo match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scoring {
+    /// Query trigrams found in the item, which favors long items holding
+    /// more trigrams overall.
+    #[default]
+    Hits,
+    /// For each typo'd query word, the Jaccard similarity of its trigram
+    /// set to that of the closest item word, averaged over those words.
+    Jaccard,
+    /// Cosine similarity of the word and trigram counts of the whole query
+    /// and the item, so a word repeated in the item weighs more. Also
+    /// orders the items matched by known words alone.
+    Cosine,
+}
+
+/// Order of results tied on everything the ranking weighs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tiebreak {
+    /// By item text, so results read the same whatever the build order.
+    #[default]
+    Text,
+    /// In the order the items were given, so a corpus sorted by importance
+    /// keeps that order among equals.
+    Insertion,
 }
 
+/// Caller-supplied text cleanup for items and queries.
+pub(crate) type Normalizer = dyn Fn(&str) -> Cow<'_, str> + Send + Sync;
+
+/// Caller-supplied comparison of two results.
+pub(crate) type Order = dyn Fn(&Match, &Match) -> Ordering + Send + Sync;
+
 impl Default for QuickMatchConfig {
     fn default() -> Self {
         Self {
+            match_mode: MatchMode::Hybrid,
             separators: DEFAULT_SEPARATORS,
+            split_when: None,
+            unicode_separators: false,
             limit: DEFAULT_LIMIT,
             trigram_budget: DEFAULT_TRIGRAM_BUDGET,
+            per_word_budget: false,
+            rarest_first: false,
+            auto_trigram_budget: false,
+            pool_shortcut: false,
+            max_edits: 0,
+            edit_expansions: 0,
+            bk_tree: false,
+            sorted_postings: false,
+            trigram_filter: false,
+            lazy_trigrams: false,
+            words_only: false,
             min_score: DEFAULT_MIN_SCORE,
+            min_hit_ratio: DEFAULT_MIN_HIT_RATIO,
+            scoring: Scoring::Hits,
+            positional_trigrams: false,
+            skip_grams: false,
+            keyboard_layout: None,
+            acronyms: false,
+            #[cfg(feature = "phonetic")]
+            phonetic: false,
+            dedup: false,
+            #[cfg(feature = "unicode")]
+            graphemes: false,
+            byte_trigrams: false,
+            cjk_bigrams: false,
+            nfkc: false,
+            number_words: false,
+            units: false,
+            joiners: None,
+            symbols: Symbols::Keep,
+            case_folding: CaseFolding::Ascii,
+            split_digits: false,
+            paths: false,
+            web_addresses: false,
+            phrases: false,
+            min_relevance: DEFAULT_MIN_RELEVANCE,
+            min_coverage: 0.0,
+            any_word: false,
+            prefix_expansion: None,
+            infix_expansion: None,
+            weight_blend: 0.1,
+            collapse: None,
+            max_query_len: None,
+            time_budget: None,
+            parallelism: 1,
+            parallel_threshold: DEFAULT_PARALLEL_THRESHOLD,
+            max_candidates: None,
+            metrics: None,
+            slow_query: None,
+            normalizer: None,
+            order: None,
+            tiebreak: Tiebreak::Text,
+            input_order: false,
+            clamped: vec![],
         }
     }
 }
@@ -47,13 +552,181 @@ impl QuickMatchConfig {
         Self::default()
     }
 
+    /// Config picked from a sample of `items`:
+    /// - separators: the defaults, plus `.` and `,` when they show up
+    ///   inside at least a tenth of the items ("com.example.app")
+    /// - trigram budget: the average word length (within 3 to 9), since
+    ///   longer words have more trigrams worth probing
+    /// - limit: a hundredth of the items (within 10 to 100), so small
+    ///   lists like command palettes return a short page
+    pub fn tuned_for<S: AsRef<str>>(items: &[S]) -> Self {
+        let step = items.len().div_ceil(TUNING_SAMPLE).max(1);
+        let sample = items.iter().step_by(step).map(|item| item.as_ref());
+
+        let (mut sampled, mut dotted, mut commaed) = (0, 0, 0);
+        let (mut word_count, mut word_len) = (0, 0);
+        for item in sample {
+            sampled += 1;
+            dotted += usize::from(item.trim_matches('.').contains('.'));
+            commaed += usize::from(item.trim_matches(',').contains(','));
+            for word in item.split(|c| DEFAULT_SEPARATORS.contains(&c) || c == '.' || c == ',') {
+                if !word.is_empty() {
+                    word_count += 1;
+                    word_len += word.chars().count();
+                }
+            }
+        }
+
+        let frequent = |count: usize| sampled > 0 && count * 10 >= sampled;
+        let separators: &'static [char] = match (frequent(dotted), frequent(commaed)) {
+            (false, false) => DEFAULT_SEPARATORS,
+            (true, false) => &['_', '-', ' ', ':', '/', '.'],
+            (false, true) => &['_', '-', ' ', ':', '/', ','],
+            (true, true) => &['_', '-', ' ', ':', '/', '.', ','],
+        };
+        let avg_word_len = word_len.div_ceil(word_count.max(1));
+
+        Self::default()
+            .with_separators(separators)
+            .with_trigram_budget(avg_word_len.clamp(3, 9))
+            .with_limit((items.len() / 100).clamp(10, DEFAULT_LIMIT))
+    }
+
+    /// Search-as-you-type over short labels: a short page, filled from the
+    /// known words alone when they suffice, and adjacent letters typed the
+    /// wrong way round ("thier") still finding the word.
+    pub fn for_autocomplete() -> Self {
+        Self::default()
+            .with_limit(10)
+            .with_pool_shortcut(true)
+            .with_skip_grams(true)
+    }
+
+    /// Identifiers such as `std::fs::read_to_string` or `sha256_digest`:
+    /// split on `.` as well, digit runs as words of their own, and initials
+    /// ("sd") finding the symbol. Symbol words are short, so fewer
+    /// trigrams are worth probing.
+    pub fn for_code_symbols() -> Self {
+        Self::default()
+            .with_separators(CODE_SEPARATORS)
+            .with_split_digits(true)
+            .with_acronyms(true)
+            .with_trigram_budget(4)
+            .with_limit(20)
+    }
+
+    /// File names and paths: `paths` mode, with digit runs split off
+    /// ("img_2024").
+    pub fn for_filenames() -> Self {
+        Self::default()
+            .with_paths(true)
+            .with_split_digits(true)
+            .with_limit(50)
+    }
+
+    /// People's names: split on apostrophes and initials' dots, full-width
+    /// forms folded, and every name of the query allowed its own typos, up
+    /// to one edit found directly.
+    pub fn for_person_names() -> Self {
+        Self::default()
+            .with_separators(NAME_SEPARATORS)
+            .with_nfkc(true)
+            .with_per_word_budget(true)
+            .with_trigram_budget(9)
+            .with_max_edits(1)
+            .with_limit(20)
+    }
+
+    /// The config, or the first value a setter had to bring into range,
+    /// e.g. a limit of 0 raised to 1. Setters otherwise clamp silently.
+    pub fn try_build(self) -> Result<Self, ConfigError> {
+        match self.clamped.first() {
+            Some(error) => Err(error.clone()),
+            None => Ok(self),
+        }
+    }
+
+    /// `used`, noting it when it isn't the `given` value. Setting an option
+    /// again replaces its earlier note.
+    fn check<T: PartialEq + Display>(&mut self, option: &'static str, given: T, used: T) -> T {
+        self.clamped.retain(|error| error.option != option);
+        if given != used {
+            self.clamped.push(ConfigError {
+                option,
+                given: given.to_string(),
+                used: used.to_string(),
+            });
+        }
+        used
+    }
+
+    pub fn with_match_mode(mut self, match_mode: MatchMode) -> Self {
+        self.match_mode = match_mode;
+        self
+    }
+
     pub fn with_limit(mut self, limit: usize) -> Self {
-        self.limit = limit.max(1);
+        self.limit = self.check("limit", limit, limit.max(1));
         self
     }
 
     pub fn with_trigram_budget(mut self, trigram_budget: usize) -> Self {
-        self.trigram_budget = trigram_budget.clamp(0, 20);
+        self.trigram_budget = self.check("trigram_budget", trigram_budget, trigram_budget.min(20));
+        self
+    }
+
+    pub fn with_per_word_budget(mut self, per_word_budget: bool) -> Self {
+        self.per_word_budget = per_word_budget;
+        self
+    }
+
+    pub fn with_rarest_first(mut self, rarest_first: bool) -> Self {
+        self.rarest_first = rarest_first;
+        self
+    }
+
+    pub fn with_auto_trigram_budget(mut self, auto_trigram_budget: bool) -> Self {
+        self.auto_trigram_budget = auto_trigram_budget;
+        self
+    }
+
+    pub fn with_pool_shortcut(mut self, pool_shortcut: bool) -> Self {
+        self.pool_shortcut = pool_shortcut;
+        self
+    }
+
+    pub fn with_max_edits(mut self, max_edits: usize) -> Self {
+        self.max_edits = self.check("max_edits", max_edits, max_edits.min(2));
+        self
+    }
+
+    pub fn with_edit_expansions(mut self, edit_expansions: usize) -> Self {
+        self.edit_expansions = edit_expansions;
+        self
+    }
+
+    pub fn with_bk_tree(mut self, bk_tree: bool) -> Self {
+        self.bk_tree = bk_tree;
+        self
+    }
+
+    pub fn with_sorted_postings(mut self, sorted_postings: bool) -> Self {
+        self.sorted_postings = sorted_postings;
+        self
+    }
+
+    pub fn with_trigram_filter(mut self, trigram_filter: bool) -> Self {
+        self.trigram_filter = trigram_filter;
+        self
+    }
+
+    pub fn with_lazy_trigrams(mut self, lazy_trigrams: bool) -> Self {
+        self.lazy_trigrams = lazy_trigrams;
+        self
+    }
+
+    pub fn with_words_only(mut self, words_only: bool) -> Self {
+        self.words_only = words_only;
         self
     }
 
@@ -62,11 +735,245 @@ impl QuickMatchConfig {
         self
     }
 
+    /// Splits words on every char `split` accepts as well, e.g.
+    /// `with_split_when(|c| !c.is_alphanumeric())`.
+    pub fn with_split_when(mut self, split: fn(char) -> bool) -> Self {
+        self.split_when = Some(split);
+        self
+    }
+
+    /// Splits words on every Unicode whitespace or punctuation char as
+    /// well, not only on `separators`.
+    pub fn with_unicode_separators(mut self, unicode_separators: bool) -> Self {
+        self.unicode_separators = unicode_separators;
+        self
+    }
+
     pub fn with_min_score(mut self, min_score: usize) -> Self {
-        self.min_score = min_score.max(1);
+        self.min_score = self.check("min_score", min_score, min_score.max(1));
+        self
+    }
+
+    pub fn with_min_hit_ratio(mut self, min_hit_ratio: f32) -> Self {
+        self.min_hit_ratio = self.check(
+            "min_hit_ratio",
+            min_hit_ratio,
+            min_hit_ratio.clamp(0.0, 1.0),
+        );
+        self
+    }
+
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    pub fn with_positional_trigrams(mut self, positional_trigrams: bool) -> Self {
+        self.positional_trigrams = positional_trigrams;
+        self
+    }
+
+    pub fn with_skip_grams(mut self, skip_grams: bool) -> Self {
+        self.skip_grams = skip_grams;
+        self
+    }
+
+    pub fn with_keyboard_layout(mut self, keyboard_layout: KeyboardLayout) -> Self {
+        self.keyboard_layout = Some(keyboard_layout);
+        self
+    }
+
+    pub fn with_acronyms(mut self, acronyms: bool) -> Self {
+        self.acronyms = acronyms;
+        self
+    }
+
+    #[cfg(feature = "phonetic")]
+    pub fn with_phonetic(mut self, phonetic: bool) -> Self {
+        self.phonetic = phonetic;
+        self
+    }
+
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    #[cfg(feature = "unicode")]
+    pub fn with_graphemes(mut self, graphemes: bool) -> Self {
+        self.graphemes = graphemes;
+        self
+    }
+
+    pub fn with_byte_trigrams(mut self, byte_trigrams: bool) -> Self {
+        self.byte_trigrams = byte_trigrams;
+        self
+    }
+
+    pub fn with_cjk_bigrams(mut self, cjk_bigrams: bool) -> Self {
+        self.cjk_bigrams = cjk_bigrams;
+        self
+    }
+
+    pub fn with_nfkc(mut self, nfkc: bool) -> Self {
+        self.nfkc = nfkc;
+        self
+    }
+
+    pub fn with_number_words(mut self, number_words: bool) -> Self {
+        self.number_words = number_words;
+        self
+    }
+
+    pub fn with_units(mut self, units: bool) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn with_joiners(mut self, joiners: Joiners) -> Self {
+        self.joiners = Some(joiners);
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: Symbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn with_case_folding(mut self, case_folding: CaseFolding) -> Self {
+        self.case_folding = case_folding;
+        self
+    }
+
+    pub fn with_paths(mut self, paths: bool) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    pub fn with_web_addresses(mut self, web_addresses: bool) -> Self {
+        self.web_addresses = web_addresses;
+        self
+    }
+
+    pub fn with_split_digits(mut self, split_digits: bool) -> Self {
+        self.split_digits = split_digits;
+        self
+    }
+
+    pub fn with_phrases(mut self, phrases: bool) -> Self {
+        self.phrases = phrases;
+        self
+    }
+
+    pub fn with_min_relevance(mut self, min_relevance: f32) -> Self {
+        self.min_relevance = self.check(
+            "min_relevance",
+            min_relevance,
+            min_relevance.clamp(0.0, 1.0),
+        );
+        self
+    }
+
+    pub fn with_min_coverage(mut self, min_coverage: f32) -> Self {
+        self.min_coverage = self.check("min_coverage", min_coverage, min_coverage.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn with_any_word(mut self, any_word: bool) -> Self {
+        self.any_word = any_word;
+        self
+    }
+
+    pub fn with_prefix_expansion(mut self, max_words: usize) -> Self {
+        self.prefix_expansion = Some(self.check("prefix_expansion", max_words, max_words.max(1)));
+        self
+    }
+
+    pub fn with_infix_expansion(mut self, max_words: usize) -> Self {
+        self.infix_expansion = Some(self.check("infix_expansion", max_words, max_words.max(1)));
+        self
+    }
+
+    pub fn with_weight_blend(mut self, weight_blend: f32) -> Self {
+        self.weight_blend = self.check("weight_blend", weight_blend, weight_blend.max(0.0));
+        self
+    }
+
+    pub fn with_collapse(mut self, threshold: f32) -> Self {
+        self.collapse = Some(self.check("collapse", threshold, threshold.clamp(0.0, 1.0)));
+        self
+    }
+
+    pub fn with_max_query_len(mut self, max_query_len: usize) -> Self {
+        self.max_query_len = Some(max_query_len);
+        self
+    }
+
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    pub fn with_parallelism(mut self, threads: usize) -> Self {
+        self.parallelism = threads;
         self
     }
 
+    pub fn with_parallel_threshold(mut self, items: usize) -> Self {
+        self.parallel_threshold = items;
+        self
+    }
+
+    pub fn with_max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates =
+            Some(self.check("max_candidates", max_candidates, max_candidates.max(1)));
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_slow_query(
+        mut self,
+        threshold: Duration,
+        on_slow: impl Fn(&SlowQuery) + Send + Sync + 'static,
+    ) -> Self {
+        self.slow_query = Some((threshold, Arc::new(on_slow)));
+        self
+    }
+
+    pub fn with_normalizer(
+        mut self,
+        normalizer: impl Fn(&str) -> Cow<'_, str> + Send + Sync + 'static,
+    ) -> Self {
+        self.normalizer = Some(Arc::new(normalizer));
+        self
+    }
+
+    pub fn with_order(
+        mut self,
+        order: impl Fn(&Match, &Match) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        self.order = Some(Arc::new(order));
+        self
+    }
+
+    pub fn with_tiebreak(mut self, tiebreak: Tiebreak) -> Self {
+        self.tiebreak = tiebreak;
+        self
+    }
+
+    pub fn with_input_order(mut self, input_order: bool) -> Self {
+        self.input_order = input_order;
+        self
+    }
+
+    pub fn match_mode(&self) -> MatchMode {
+        self.match_mode
+    }
+
     pub fn limit(&self) -> usize {
         self.limit
     }
@@ -75,11 +982,287 @@ impl QuickMatchConfig {
         self.trigram_budget
     }
 
+    pub fn per_word_budget(&self) -> bool {
+        self.per_word_budget
+    }
+
+    pub fn rarest_first(&self) -> bool {
+        self.rarest_first
+    }
+
+    pub fn auto_trigram_budget(&self) -> bool {
+        self.auto_trigram_budget
+    }
+
+    pub fn pool_shortcut(&self) -> bool {
+        self.pool_shortcut
+    }
+
+    pub fn max_edits(&self) -> usize {
+        self.max_edits
+    }
+
+    pub fn edit_expansions(&self) -> usize {
+        self.edit_expansions
+    }
+
+    pub fn bk_tree(&self) -> bool {
+        self.bk_tree
+    }
+
+    pub fn sorted_postings(&self) -> bool {
+        self.sorted_postings
+    }
+
+    pub fn trigram_filter(&self) -> bool {
+        self.trigram_filter
+    }
+
+    pub fn lazy_trigrams(&self) -> bool {
+        self.lazy_trigrams
+    }
+
+    pub fn words_only(&self) -> bool {
+        self.words_only
+    }
+
     pub fn separators(&self) -> &[char] {
         self.separators
     }
 
+    pub fn split_when(&self) -> Option<fn(char) -> bool> {
+        self.split_when
+    }
+
+    pub fn unicode_separators(&self) -> bool {
+        self.unicode_separators
+    }
+
+    /// Whether anything splits words on top of `separators`.
+    pub(crate) fn splits_more(&self) -> bool {
+        self.split_when.is_some() || self.unicode_separators
+    }
+
+    /// Whether `c` splits words on top of `separators`.
+    pub(crate) fn splits(&self, c: char) -> bool {
+        self.split_when.is_some_and(|split| split(c))
+            || (self.unicode_separators && punctuation::is_separator(c))
+    }
+
+    /// The settings that decide how items are split into words and what
+    /// gets indexed, recorded in saved files so they're loaded under the
+    /// same ones. Caller-supplied functions only count as set or not.
+    pub(crate) fn index_settings(&self) -> String {
+        #[cfg(feature = "phonetic")]
+        let phonetic = self.phonetic;
+        #[cfg(not(feature = "phonetic"))]
+        let phonetic = false;
+        format!(
+            "separators={:?} split_when={} unicode_separators={} normalizer={} nfkc={} \
+             case_folding={:?} symbols={:?} joiners={:?} units={} number_words={} \
+             split_digits={} paths={} web_addresses={} words_only={} dedup={} \
+             byte_trigrams={} graphemes={} skip_grams={} positional_trigrams={} \
+             cjk_bigrams={} acronyms={} phonetic={} phrases={} bk_tree={} prefix_expansion={:?} \
+             infix_expansion={:?}",
+            self.separators.iter().collect::<String>(),
+            self.split_when.is_some(),
+            self.unicode_separators,
+            self.normalizer.is_some(),
+            self.nfkc(),
+            self.case_folding(),
+            self.symbols(),
+            self.joiners(),
+            self.units(),
+            self.number_words(),
+            self.split_digits(),
+            self.paths(),
+            self.web_addresses(),
+            self.words_only(),
+            self.dedup(),
+            self.byte_trigrams(),
+            self.grapheme_grams(),
+            self.skip_grams(),
+            self.positional_trigrams(),
+            self.cjk_bigrams(),
+            self.acronyms(),
+            phonetic,
+            self.phrases(),
+            self.bk_tree(),
+            self.prefix_expansion(),
+            self.infix_expansion(),
+        )
+    }
+
     pub fn min_score(&self) -> usize {
         self.min_score
     }
+
+    pub fn min_hit_ratio(&self) -> f32 {
+        self.min_hit_ratio
+    }
+
+    pub fn scoring(&self) -> Scoring {
+        self.scoring
+    }
+
+    pub fn positional_trigrams(&self) -> bool {
+        self.positional_trigrams
+    }
+
+    pub fn skip_grams(&self) -> bool {
+        self.skip_grams
+    }
+
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.keyboard_layout
+    }
+
+    pub fn acronyms(&self) -> bool {
+        self.acronyms
+    }
+
+    pub fn dedup(&self) -> bool {
+        self.dedup
+    }
+
+    #[cfg(feature = "phonetic")]
+    pub fn phonetic(&self) -> bool {
+        self.phonetic
+    }
+
+    #[cfg(feature = "unicode")]
+    pub fn graphemes(&self) -> bool {
+        self.graphemes
+    }
+
+    /// Whether grams are windowed over grapheme clusters, which only the
+    /// `unicode` feature can turn on.
+    pub(crate) fn grapheme_grams(&self) -> bool {
+        #[cfg(feature = "unicode")]
+        return self.graphemes && !self.byte_trigrams;
+        #[cfg(not(feature = "unicode"))]
+        false
+    }
+
+    pub fn byte_trigrams(&self) -> bool {
+        self.byte_trigrams
+    }
+
+    pub fn cjk_bigrams(&self) -> bool {
+        self.cjk_bigrams
+    }
+
+    pub fn nfkc(&self) -> bool {
+        self.nfkc
+    }
+
+    pub fn number_words(&self) -> bool {
+        self.number_words
+    }
+
+    pub fn units(&self) -> bool {
+        self.units
+    }
+
+    pub fn joiners(&self) -> Option<Joiners> {
+        self.joiners
+    }
+
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+    }
+
+    pub fn case_folding(&self) -> CaseFolding {
+        self.case_folding
+    }
+
+    pub fn paths(&self) -> bool {
+        self.paths
+    }
+
+    pub fn web_addresses(&self) -> bool {
+        self.web_addresses
+    }
+
+    pub fn split_digits(&self) -> bool {
+        self.split_digits
+    }
+
+    pub fn phrases(&self) -> bool {
+        self.phrases
+    }
+
+    pub fn min_relevance(&self) -> f32 {
+        self.min_relevance
+    }
+
+    pub fn min_coverage(&self) -> f32 {
+        self.min_coverage
+    }
+
+    pub fn any_word(&self) -> bool {
+        self.any_word
+    }
+
+    pub fn prefix_expansion(&self) -> Option<usize> {
+        self.prefix_expansion
+    }
+
+    pub fn infix_expansion(&self) -> Option<usize> {
+        self.infix_expansion
+    }
+
+    pub fn weight_blend(&self) -> f32 {
+        self.weight_blend
+    }
+
+    pub fn collapse(&self) -> Option<f32> {
+        self.collapse
+    }
+
+    pub fn max_query_len(&self) -> Option<usize> {
+        self.max_query_len
+    }
+
+    pub fn time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+
+    pub fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+
+    pub fn parallel_threshold(&self) -> usize {
+        self.parallel_threshold
+    }
+
+    pub fn max_candidates(&self) -> Option<usize> {
+        self.max_candidates
+    }
+
+    pub fn metrics(&self) -> Option<&dyn Metrics> {
+        self.metrics.as_deref()
+    }
+
+    pub(crate) fn slow_query(&self) -> Option<(Duration, &OnSlowQuery)> {
+        self.slow_query
+            .as_ref()
+            .map(|(threshold, on_slow)| (*threshold, &**on_slow))
+    }
+
+    pub(crate) fn normalizer(&self) -> Option<&Normalizer> {
+        self.normalizer.as_deref()
+    }
+
+    pub(crate) fn order(&self) -> Option<&Order> {
+        self.order.as_deref()
+    }
+
+    pub fn tiebreak(&self) -> Tiebreak {
+        self.tiebreak
+    }
+
+    pub fn input_order(&self) -> bool {
+        self.input_order
+    }
 }
@@ -0,0 +1,115 @@
+/// Levenshtein distance between `a` and `b` in chars: the fewest
+/// insertions, deletions and substitutions turning one into the other.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != cb);
+            row[j + 1] = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        (prev, row) = (row, prev);
+    }
+    prev[b.len()]
+}
+
+/// The words one edit from an ASCII `word` over lowercase letters and
+/// digits: deletions, swaps of neighbors, substitutions, then insertions,
+/// so the cheapest to check and likeliest come first.
+pub(crate) fn single_edits(word: &str) -> impl Iterator<Item = String> + '_ {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let bytes = word.as_bytes();
+    let edit = move |at: usize, skip: usize, insert: &[u8]| {
+        let mut variant = Vec::with_capacity(bytes.len() + 1);
+        variant.extend_from_slice(&bytes[..at]);
+        variant.extend_from_slice(insert);
+        variant.extend_from_slice(&bytes[at + skip..]);
+        String::from_utf8(variant).unwrap_or_default()
+    };
+    let deletions = (0..bytes.len()).map(move |i| edit(i, 1, &[]));
+    let swaps = (0..bytes.len().saturating_sub(1))
+        .filter(move |&i| bytes[i] != bytes[i + 1])
+        .map(move |i| edit(i, 2, &[bytes[i + 1], bytes[i]]));
+    let substitutions = (0..bytes.len()).flat_map(move |i| {
+        ALPHABET
+            .iter()
+            .filter(move |&&c| c != bytes[i])
+            .map(move |&c| edit(i, 1, &[c]))
+    });
+    let insertions =
+        (0..=bytes.len()).flat_map(move |i| ALPHABET.iter().map(move |&c| edit(i, 0, &[c])));
+    deletions
+        .chain(swaps)
+        .chain(substitutions)
+        .chain(insertions)
+}
+
+/// Accepts the words within `max` edits of a fixed word, fed one char at a
+/// time. A state is a row of the Levenshtein table, so words sharing a
+/// prefix share the states for it.
+pub(crate) struct LevenshteinAutomaton {
+    word: Vec<char>,
+    max: usize,
+}
+
+impl LevenshteinAutomaton {
+    pub(crate) fn new(word: &str, max: usize) -> Self {
+        Self {
+            word: word.chars().collect(),
+            max,
+        }
+    }
+
+    fn start(&self) -> Vec<usize> {
+        (0..=self.word.len()).collect()
+    }
+
+    fn step(&self, row: &[usize], c: char) -> Vec<usize> {
+        let mut next = Vec::with_capacity(row.len());
+        next.push(row[0] + 1);
+        for (j, &wc) in self.word.iter().enumerate() {
+            let substitute = row[j] + usize::from(wc != c);
+            next.push(substitute.min(row[j + 1] + 1).min(next[j] + 1));
+        }
+        next
+    }
+
+    fn is_match(&self, row: &[usize]) -> bool {
+        row[self.word.len()] <= self.max
+    }
+
+    /// Whether some continuation could still match.
+    fn can_match(&self, row: &[usize]) -> bool {
+        row.iter().min().is_some_and(|&d| d <= self.max)
+    }
+
+    /// The accepted words of `sorted`, walking it like a trie: each word
+    /// reuses the states of the prefix it shares with the one before, and
+    /// stops as soon as no continuation can match.
+    pub(crate) fn matches<'w>(&self, sorted: impl Iterator<Item = &'w str>) -> Vec<&'w str> {
+        let mut accepted = vec![];
+        let mut rows = vec![self.start()];
+        let mut prev: Vec<char> = vec![];
+        for word in sorted {
+            let chars: Vec<char> = word.chars().collect();
+            let common = prev.iter().zip(&chars).take_while(|(a, b)| a == b).count();
+            rows.truncate(common.min(rows.len() - 1) + 1);
+            while rows.len() <= chars.len() {
+                let row = &rows[rows.len() - 1];
+                if !self.can_match(row) {
+                    break;
+                }
+                let next = self.step(row, chars[rows.len() - 1]);
+                rows.push(next);
+            }
+            if rows.len() == chars.len() + 1 && self.is_match(&rows[chars.len()]) {
+                accepted.push(word);
+            }
+            prev = chars;
+        }
+        accepted
+    }
+}
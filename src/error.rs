@@ -0,0 +1,98 @@
+use std::{error::Error, fmt};
+
+/// Why `QuickMatch::try_matches` rejected a query without searching.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// Nothing is left once the query is normalized.
+    Empty,
+    /// The normalized query is longer than the config's `max_query_len`, or
+    /// than the longest item by a margin when none is set.
+    TooLong { len: usize, max: usize },
+    /// The query holds separators only.
+    NoWords,
+    /// Every query word is longer than any indexed word could match.
+    WordsTooLong { max: usize },
+    /// More query words than any item holds, by a margin.
+    TooManyWords { count: usize, max: usize },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "query is empty"),
+            Self::TooLong { len, max } => {
+                write!(f, "query is {len} bytes long, over the maximum of {max}")
+            }
+            Self::NoWords => write!(f, "query holds only separators"),
+            Self::WordsTooLong { max } => {
+                write!(f, "every query word is over the maximum of {max} bytes")
+            }
+            Self::TooManyWords { count, max } => {
+                write!(f, "query has {count} words, over the maximum of {max}")
+            }
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+/// A config value out of range, from `QuickMatchConfig::try_build`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// The option, as named by its setter without `with_`.
+    pub option: &'static str,
+    pub given: String,
+    /// The value the setter used instead.
+    pub used: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} is out of range, {} would be used",
+            self.option, self.given, self.used
+        )
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Why a saved index was refused, carried by the `InvalidData` error of
+/// `OwnedQuickMatch::load` and `UpdateLog`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// The file doesn't start with the header `save` writes.
+    NotAnIndex,
+    /// Saved in a format version this build doesn't read.
+    Version { found: String, supported: u32 },
+    /// Built under other index settings than the config loaded with.
+    ConfigMismatch { saved: String, given: String },
+    /// The file ends before its checksum line, e.g. after a partial copy.
+    Truncated,
+    /// The items don't add up to the saved checksum.
+    Checksum { saved: String, computed: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnIndex => write!(f, "not a saved quickmatch index"),
+            Self::Version { found, supported } => write!(
+                f,
+                "index format version {found} is not supported, only version {supported} is"
+            ),
+            Self::ConfigMismatch { saved, given } => write!(
+                f,
+                "index was built with settings [{saved}] but is loaded with [{given}]"
+            ),
+            Self::Truncated => write!(f, "index file is truncated, its checksum is missing"),
+            Self::Checksum { saved, computed } => write!(
+                f,
+                "index file is corrupt, its checksum is {computed} instead of {saved}"
+            ),
+        }
+    }
+}
+
+impl Error for LoadError {}
@@ -0,0 +1,58 @@
+/// Intersection of sorted, duplicate-free id lists. Walks the shortest
+/// list and finds each of its ids in the others by galloping: doubling the
+/// step from the last position, then a binary search within the last step,
+/// so a short list against a long one costs little more than its own length
+/// and every probe moves forward through contiguous memory.
+pub(crate) fn intersect(lists: &mut [&[u32]]) -> Vec<u32> {
+    lists.sort_unstable_by_key(|list| list.len());
+    let Some((shortest, rest)) = lists.split_first() else {
+        return vec![];
+    };
+    let mut positions = vec![0; rest.len()];
+    let mut out = vec![];
+    'ids: for &id in *shortest {
+        for (list, pos) in rest.iter().zip(&mut positions) {
+            *pos += gallop(&list[*pos..], id);
+            if list.get(*pos) != Some(&id) {
+                if *pos == list.len() {
+                    break 'ids;
+                }
+                continue 'ids;
+            }
+        }
+        out.push(id);
+    }
+    out
+}
+
+/// Index of the first id of `list` not below `target`.
+fn gallop(list: &[u32], target: u32) -> usize {
+    let mut end = 1;
+    while end < list.len() && list[end - 1] < target {
+        end *= 2;
+    }
+    let start = end / 2;
+    let end = end.min(list.len());
+    start + lower_bound(&list[start..end], target)
+}
+
+/// Ids compared at once at the end of a search: a fixed-width count has no
+/// branches, so it compiles to vector compares on stable Rust.
+const LANES: usize = 16;
+
+/// Index of the first id of `list` not below `target`: binary search down
+/// to a block of `LANES` ids, then a count of the ids below `target`, which
+/// in a sorted block is that index.
+fn lower_bound(list: &[u32], target: u32) -> usize {
+    let (mut lo, mut len) = (0, list.len());
+    while len > LANES {
+        let half = len / 2;
+        if list[lo + half] < target {
+            lo += half + 1;
+            len -= half + 1;
+        } else {
+            len = half;
+        }
+    }
+    lo + list[lo..lo + len].iter().filter(|&&id| id < target).count()
+}
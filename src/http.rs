@@ -0,0 +1,191 @@
+use std::{
+    hash::BuildHasher,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{Mutex, mpsc},
+    thread,
+    time::Duration,
+};
+
+use crate::{Match, QuickMatch};
+
+/// Connections answered at once; as many more wait for a free worker
+/// before accepting stalls.
+const WORKERS: usize = 8;
+/// Longest request line accepted, past which the answer is 414.
+const MAX_REQUEST_LINE: u64 = 8 * 1024;
+/// Most header bytes accepted, past which the answer is 431.
+const MAX_HEADERS: u64 = 16 * 1024;
+/// How long a client may stall a read or a write before it is dropped.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves `GET /search?q=..&limit=..` over `qm`, answering with the scored
+/// results as JSON (`[{"item":"..","score":1.0}]`). Connections are
+/// answered by a fixed pool of worker threads, one response each, and
+/// dropped when the client stalls or sends an oversized request. `limit`
+/// defaults to the matcher's own and is capped by it. Runs until
+/// accepting fails.
+pub fn serve<S>(qm: &QuickMatch<'_, S>, listener: TcpListener) -> io::Result<()>
+where
+    S: BuildHasher + Default + Clone + Sync,
+{
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(WORKERS);
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| {
+                loop {
+                    let Ok(stream) = receiver.lock().unwrap().recv() else {
+                        break;
+                    };
+                    // A client hanging up mid-request only ends its own
+                    // connection
+                    handle(qm, stream).ok();
+                }
+            });
+        }
+        // Owned here so that returning stops the workers
+        let sender = sender;
+        for stream in listener.incoming() {
+            if sender.send(stream?).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn handle<S: BuildHasher + Default + Clone>(
+    qm: &QuickMatch<'_, S>,
+    stream: TcpStream,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let (status, body) = match read_request(&mut reader)? {
+        Ok(target) => answer(qm, &target),
+        Err(refusal) => {
+            respond(&stream, refusal, "{\"error\":\"request too large\"}")?;
+            // Closing with input unread would reset the connection before
+            // the client reads the answer, so skip a bounded amount of it
+            stream.shutdown(Shutdown::Write)?;
+            io::copy(
+                &mut reader.take(MAX_REQUEST_LINE + MAX_HEADERS),
+                &mut io::sink(),
+            )?;
+            return Ok(());
+        }
+    };
+    respond(&stream, status, &body)
+}
+
+/// The request line of the next request, read along with its headers, or
+/// the status refusing it when either is too long.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<String, &'static str>> {
+    let Some(request) = read_line(reader, MAX_REQUEST_LINE)? else {
+        return Ok(Err("414 URI Too Long"));
+    };
+    // Drain the headers, which nothing here needs
+    let mut left = MAX_HEADERS;
+    loop {
+        let Some(line) = read_line(reader, left)? else {
+            return Ok(Err("431 Request Header Fields Too Large"));
+        };
+        left -= line.len() as u64;
+        if line.len() <= 2 {
+            return Ok(Ok(request));
+        }
+    }
+}
+
+/// The next line, or `None` when it runs past `max` bytes.
+fn read_line(reader: &mut impl BufRead, max: u64) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.take(max).read_line(&mut line)?;
+    Ok((line.ends_with('\n') || (line.len() as u64) < max).then_some(line))
+}
+
+/// The status and JSON body answering a request line.
+fn answer<S: BuildHasher + Default + Clone>(
+    qm: &QuickMatch<'_, S>,
+    request: &str,
+) -> (&'static str, String) {
+    let error = |status, message: &str| (status, format!("{{\"error\":\"{message}\"}}"));
+    let target = match request.trim_end().split(' ').collect::<Vec<_>>()[..] {
+        ["GET", target, _] => target,
+        _ => return error("405 Method Not Allowed", "GET only"),
+    };
+    let (path, params) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/search" {
+        return error("404 Not Found", "not found");
+    }
+
+    let mut query = String::new();
+    let mut limit = qm.config().limit();
+    for (key, value) in params.split('&').filter_map(|param| param.split_once('=')) {
+        match key {
+            "q" => query = decode(value),
+            "limit" => match value.parse::<usize>() {
+                Ok(n) => limit = limit.min(n.max(1)),
+                Err(_) => return error("400 Bad Request", "bad limit"),
+            },
+            _ => {}
+        }
+    }
+
+    let mut results = qm.matches_scored(&query);
+    results.truncate(limit);
+    ("200 OK", to_json(&results))
+}
+
+fn respond(mut stream: &TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Percent-decodes a query-string value, with `+` as a space.
+fn decode(value: &str) -> String {
+    let hex = |b: u8| (b as char).to_digit(16);
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                (Some(high), Some(low)) => {
+                    out.push((high * 16 + low) as u8);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn to_json(results: &[Match]) -> String {
+    let mut json = String::from("[");
+    for (i, m) in results.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"item\":\"");
+        for c in m.item.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+                c => json.push(c),
+            }
+        }
+        json.push_str(&format!("\",\"score\":{}}}", m.score));
+    }
+    json.push(']');
+    json
+}
@@ -0,0 +1,374 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    mem,
+    path::{Path, PathBuf},
+};
+
+use rustc_hash::FxHashMap;
+
+use crate::{OwnedQuickMatch, QuickMatchConfig};
+
+enum Format {
+    Csv,
+    Jsonl,
+}
+
+/// Builds a matcher over one column of a CSV file (with a header row) or
+/// one field of a JSON Lines file, optionally keeping another column or
+/// field as each item's payload:
+///
+/// ```no_run
+/// # use quickmatch::ingest::Ingest;
+/// let products = Ingest::csv("products.csv")
+///     .key_column("name")
+///     .payload_column("id")
+///     .build()?;
+/// for (name, ids) in products.matches("usb cable") {
+///     println!("{name}: {ids:?}");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// Keys are trimmed and lowercased, as the matcher expects, and rows with
+/// an empty or missing key are skipped. A JSON `null`, object or array
+/// counts as missing.
+pub struct Ingest {
+    path: PathBuf,
+    format: Format,
+    key: String,
+    payload: Option<String>,
+    config: QuickMatchConfig,
+}
+
+impl Ingest {
+    pub fn csv(path: impl AsRef<Path>) -> Self {
+        Self::new(path.as_ref(), Format::Csv)
+    }
+
+    pub fn jsonl(path: impl AsRef<Path>) -> Self {
+        Self::new(path.as_ref(), Format::Jsonl)
+    }
+
+    fn new(path: &Path, format: Format) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            format,
+            key: String::new(),
+            payload: None,
+            config: QuickMatchConfig::default(),
+        }
+    }
+
+    /// The column (or JSONL field) holding the text to match.
+    pub fn key_column(mut self, name: &str) -> Self {
+        self.key = name.to_string();
+        self
+    }
+
+    /// The column (or JSONL field) kept as each item's payload.
+    pub fn payload_column(mut self, name: &str) -> Self {
+        self.payload = Some(name.to_string());
+        self
+    }
+
+    pub fn with_config(mut self, config: QuickMatchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Reads the file and builds the matcher. Fails on a missing CSV column
+    /// or a line that isn't a JSON object.
+    pub fn build(self) -> io::Result<Ingested> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let rows = match self.format {
+            Format::Csv => csv_rows(reader, &self.key, self.payload.as_deref())?,
+            Format::Jsonl => jsonl_rows(reader, &self.key, self.payload.as_deref())?,
+        };
+        let mut keys = Vec::with_capacity(rows.len());
+        let mut payloads: FxHashMap<String, Vec<String>> = FxHashMap::default();
+        for (key, payload) in rows {
+            let key = key.trim().to_lowercase();
+            if key.is_empty() {
+                continue;
+            }
+            if let Some(payload) = payload {
+                payloads.entry(key.clone()).or_default().push(payload);
+            }
+            keys.push(key);
+        }
+        Ok(Ingested {
+            matcher: OwnedQuickMatch::new_with(&keys, self.config),
+            payloads,
+        })
+    }
+}
+
+/// A matcher built by [`Ingest`], with the payloads of its items.
+pub struct Ingested {
+    matcher: OwnedQuickMatch,
+    payloads: FxHashMap<String, Vec<String>>,
+}
+
+impl Ingested {
+    pub fn matcher(&self) -> &OwnedQuickMatch {
+        &self.matcher
+    }
+
+    /// The payloads of every row with this key, in file order.
+    pub fn payloads(&self, item: &str) -> &[String] {
+        self.payloads.get(item).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn matches(&self, query: &str) -> Vec<(&str, &[String])> {
+        self.matcher
+            .matches(query)
+            .into_iter()
+            .map(|item| (item, self.payloads(item)))
+            .collect()
+    }
+}
+
+type Row = (String, Option<String>);
+
+fn csv_rows(mut reader: impl BufRead, key: &str, payload: Option<&str>) -> io::Result<Vec<Row>> {
+    let header = read_record(&mut reader)?.unwrap_or_default();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| invalid(format!("no column named {name:?}")))
+    };
+    let key_at = column(key)?;
+    let payload_at = payload.map(column).transpose()?;
+
+    let mut rows = vec![];
+    while let Some(mut record) = read_record(&mut reader)? {
+        let payload = payload_at.map(|at| record.get_mut(at).map(mem::take).unwrap_or_default());
+        let key = record.get_mut(key_at).map(mem::take).unwrap_or_default();
+        rows.push((key, payload));
+    }
+    Ok(rows)
+}
+
+/// The fields of the next CSV record, which spans several lines when a
+/// quoted field holds line breaks.
+fn read_record(reader: &mut impl BufRead) -> io::Result<Option<Vec<String>>> {
+    let mut record = String::new();
+    loop {
+        if reader.read_line(&mut record)? == 0 {
+            // A quote left open runs to the end of the file
+            return Ok((!record.is_empty()).then(|| split_record(&record).0));
+        }
+        let (fields, open) = split_record(&record);
+        if !open {
+            return Ok(Some(fields));
+        }
+    }
+}
+
+/// The fields of a CSV record, and whether it ends inside a quoted field.
+/// Quotes inside quoted fields are doubled; a quote opens one only at the
+/// start of a field, and is kept as written anywhere else.
+fn split_record(record: &str) -> (Vec<String>, bool) {
+    let record = record.trim_end_matches(['\n', '\r']);
+    let mut fields = vec![String::new()];
+    let (mut quoted, mut at_start) = (false, true);
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if at_start => quoted = true,
+            ',' if !quoted => {
+                fields.push(String::new());
+                at_start = true;
+                continue;
+            }
+            c => field.push(c),
+        }
+        at_start = false;
+    }
+    (fields, quoted)
+}
+
+fn jsonl_rows(reader: impl BufRead, key: &str, payload: Option<&str>) -> io::Result<Vec<Row>> {
+    let mut rows = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = json_fields(&line, key, payload)
+            .ok_or_else(|| invalid(format!("line is not a JSON object: {line:?}")))?;
+        let key = fields[0].take().unwrap_or_default();
+        rows.push((key, payload.map(|_| fields[1].take().unwrap_or_default())));
+    }
+    Ok(rows)
+}
+
+/// The values of the top-level `key` and `payload` fields of a JSON
+/// object, as text: strings unescaped, numbers and literals as written.
+/// Nested objects and arrays are skipped over.
+fn json_fields(line: &str, key: &str, payload: Option<&str>) -> Option<[Option<String>; 2]> {
+    let mut json = Json {
+        bytes: line.as_bytes(),
+        at: 0,
+    };
+    let mut found = [None, None];
+    json.expect(b'{')?;
+    if json.peek() == Some(b'}') {
+        return Some(found);
+    }
+    loop {
+        let name = json.string()?;
+        json.expect(b':')?;
+        let slot = if name == key {
+            Some(0)
+        } else if Some(name.as_str()) == payload {
+            Some(1)
+        } else {
+            None
+        };
+        match (slot, json.peek()?) {
+            (Some(slot), b'"') => found[slot] = Some(json.string()?),
+            (Some(slot), b'{' | b'[') => {
+                json.skip_value()?;
+                found[slot] = None;
+            }
+            (Some(slot), _) => {
+                let start = json.at;
+                json.skip_value()?;
+                let literal = line[start..json.at].trim();
+                found[slot] = (literal != "null").then(|| literal.to_string());
+            }
+            (None, _) => json.skip_value()?,
+        }
+        match json.next()? {
+            b',' => continue,
+            b'}' => return Some(found),
+            _ => return None,
+        }
+    }
+}
+
+struct Json<'j> {
+    bytes: &'j [u8],
+    at: usize,
+}
+
+impl Json<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.bytes.get(self.at)?.is_ascii_whitespace() {
+            self.at += 1;
+        }
+        self.bytes.get(self.at).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.at += 1;
+        Some(b)
+    }
+
+    fn expect(&mut self, b: u8) -> Option<()> {
+        (self.next()? == b).then_some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut out = vec![];
+        loop {
+            let b = *self.bytes.get(self.at)?;
+            self.at += 1;
+            match b {
+                b'"' => return String::from_utf8(out).ok(),
+                b'\\' => {
+                    let escaped = *self.bytes.get(self.at)?;
+                    self.at += 1;
+                    let c = match escaped {
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => self.unicode_escape()?,
+                        b => b as char,
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                b => out.push(b),
+            }
+        }
+    }
+
+    /// The char of a `\u` escape, joining a surrogate pair.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high);
+        }
+        if self.bytes.get(self.at..self.at + 2)? != b"\\u" {
+            return None;
+        }
+        self.at += 2;
+        let low = self.hex4()?;
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low.checked_sub(0xDC00)?))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let hex = std::str::from_utf8(self.bytes.get(self.at..self.at + 4)?).ok()?;
+        self.at += 4;
+        u32::from_str_radix(hex, 16).ok()
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => {
+                self.string()?;
+            }
+            open @ (b'{' | b'[') => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.at += 1;
+                if self.peek()? == close {
+                    self.at += 1;
+                    return Some(());
+                }
+                loop {
+                    if open == b'{' {
+                        self.string()?;
+                        self.expect(b':')?;
+                    }
+                    self.skip_value()?;
+                    match self.next()? {
+                        b',' => continue,
+                        b if b == close => break,
+                        _ => return None,
+                    }
+                }
+            }
+            _ => {
+                let start = self.at;
+                while self
+                    .bytes
+                    .get(self.at)
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.at += 1;
+                }
+                if self.at == start {
+                    return None;
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,39 @@
+use std::borrow::Cow;
+
+use crate::Joiners;
+
+/// The text with apostrophes and hyphens between two letters or digits
+/// kept (as plain `'` and `-`), turned into spaces, or dropped, per
+/// `joiners`. Those anywhere else become spaces. Borrowed when there are
+/// none.
+pub(crate) fn apply(text: &str, joiners: Joiners) -> Cow<'_, str> {
+    if !text.chars().any(|c| joiner(c).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let Some(plain) = joiner(c) else {
+            out.push(c);
+            continue;
+        };
+        let inside = i > 0
+            && chars[i - 1].is_alphanumeric()
+            && chars.get(i + 1).is_some_and(|c| c.is_alphanumeric());
+        match joiners {
+            Joiners::Keep if inside => out.push(plain),
+            Joiners::Merge if inside => {}
+            _ => out.push(' '),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// The plain form of an apostrophe or hyphen.
+fn joiner(c: char) -> Option<char> {
+    match c {
+        '\'' | '\u{2019}' | '\u{02BC}' => Some('\''),
+        '-' | '\u{2010}' | '\u{2011}' => Some('-'),
+        _ => None,
+    }
+}
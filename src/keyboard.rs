@@ -0,0 +1,46 @@
+/// Physical key layout used to guess fat-finger typos ("qpple" → "apple").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Azerty,
+}
+
+impl KeyboardLayout {
+    fn rows(self) -> [&'static str; 4] {
+        match self {
+            Self::Qwerty => ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"],
+            Self::Azerty => ["1234567890", "azertyuiop", "qsdfghjklm", "wxcvbn"],
+        }
+    }
+
+    /// Keys touching `c`: both sides on its row, plus the two keys it sits
+    /// between on the rows above and below (each row is offset half a key
+    /// to the right of the one above it).
+    pub(crate) fn neighbors(self, c: char) -> impl Iterator<Item = char> {
+        let rows = self.rows();
+        let at = rows
+            .iter()
+            .enumerate()
+            .find_map(|(r, row)| row.find(c).map(|col| (r, col)));
+
+        let mut keys = vec![];
+        if let Some((r, col)) = at {
+            let mut push = |r: usize, col: Option<usize>| {
+                if let Some(key) = col.and_then(|col| rows[r].as_bytes().get(col)) {
+                    keys.push(*key as char);
+                }
+            };
+            push(r, col.checked_sub(1));
+            push(r, Some(col + 1));
+            if r > 0 {
+                push(r - 1, Some(col));
+                push(r - 1, Some(col + 1));
+            }
+            if r + 1 < rows.len() {
+                push(r + 1, col.checked_sub(1));
+                push(r + 1, Some(col));
+            }
+        }
+        keys.into_iter()
+    }
+}
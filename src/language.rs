@@ -0,0 +1,35 @@
+/// Language an item is written in, picking the extra analysis its words get
+/// at build time (see `QuickMatch::new_tagged`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// Plural words are also indexed under their singular ("cities" →
+    /// "city"), so a query for the singular finds them.
+    English,
+    /// Words are also indexed under their CJK chars and bigrams, as with
+    /// `with_cjk_bigrams`, whatever the config says.
+    Cjk,
+    /// No extra analysis.
+    Other,
+}
+
+/// Singular of an English plural ("boxes" → "box", "cities" → "city"), or
+/// `None` when the word doesn't look like one.
+pub(crate) fn singular(word: &str) -> Option<String> {
+    if !word.is_ascii() || word.len() < 4 {
+        return None;
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        return Some(format!("{stem}y"));
+    }
+    if let Some(stem) = word.strip_suffix("es")
+        && ["s", "x", "z", "ch", "sh"]
+            .iter()
+            .any(|end| stem.ends_with(end))
+    {
+        return Some(stem.to_string());
+    }
+    if word.ends_with("ss") || word.ends_with("us") || word.ends_with("is") {
+        return None;
+    }
+    word.strip_suffix('s').map(str::to_string)
+}
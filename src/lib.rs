@@ -1,173 +1,2407 @@
-use std::{iter, marker::PhantomData};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, hash_map},
+    hash::{BuildHasher, Hash},
+    iter,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, ControlFlow},
+    ptr, slice,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
 
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
 
+mod bktree;
+mod bloom;
+mod cancel;
+mod casefold;
+mod cjk;
 mod config;
+mod distance;
+mod error;
+mod gallop;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "ingest")]
+pub mod ingest;
+mod joiners;
+mod keyboard;
+mod language;
+mod live;
+mod metrics;
+mod minhash;
+mod multi;
+mod nfkc;
+mod numbers;
+mod owned;
+mod persist;
+#[cfg(feature = "phonetic")]
+mod phonetic;
+mod punctuation;
+mod query;
+mod session;
+mod shared;
+mod subsequence;
+mod symbols;
+mod topk;
+#[cfg(feature = "unicode")]
+mod unicode;
+mod units;
 
+use bktree::BkTree;
+use bloom::GramFilter;
+pub use cancel::CancelToken;
 pub use config::*;
+pub use error::{ConfigError, LoadError, QueryError};
+pub use keyboard::*;
+pub use language::Language;
+pub use live::LiveIndex;
+use metrics::OnSlowQuery;
+pub use metrics::{Metrics, Phase, SlowQuery};
+pub use multi::*;
+pub use owned::OwnedQuickMatch;
+pub use persist::{Update, UpdateLog};
+pub use query::ParsedQuery;
+pub use session::Session;
+pub use shared::*;
+use topk::TopK;
 
 /// Instant search over a list of strings.
 ///
 /// Supports exact words, prefixes ("dom" → "dominance"), joined words
 /// ("hashrate" → "hash_rate"), and typo tolerance ("suply" → "supply").
-/// Results are ranked: exact matches first, then by specificity.
-pub struct QuickMatch<'a> {
+/// Results are ranked: exact matches first, then by specificity. Remaining
+/// ties are broken by item text, so the order is the same on every run.
+///
+/// Generic over the hasher of its maps, FxHash by default; see
+/// `new_with_hasher` for e.g. a keyed SipHash (`RandomState`) when items or
+/// queries come from untrusted input.
+#[derive(Clone)]
+pub struct QuickMatch<'a, S = FxBuildHasher> {
     config: QuickMatchConfig,
+    items: Vec<&'a str>,
+    /// Item texts seen so far, kept only when built with `dedup`.
+    seen: HashSet<&'a str, S>,
+    duplicates: usize,
     max_word_count: usize,
     max_word_len: usize,
     max_query_len: usize,
-    word_index: FxHashMap<String, FxHashSet<*const str>>,
-    trigram_index: FxHashMap<[char; 3], FxHashSet<*const str>>,
+    word_index: HashMap<String, Postings<S>, S>,
+    /// `word_index` as positions in `items`, each list sorted, kept only
+    /// when built with `sorted_postings`.
+    word_ids: HashMap<String, Vec<u32>, S>,
+    /// Keys of `word_index` in order, kept only when built with
+    /// `prefix_expansion`, which leaves prefixes out of the index.
+    sorted_words: BTreeSet<String>,
+    /// Every distinct item word, in order, with the number of items
+    /// holding it, for `complete`.
+    word_counts: BTreeMap<String, usize>,
+    /// The distinct item words by edit distance, kept only when built with
+    /// `bk_tree`.
+    bk_tree: BkTree,
+    /// Left unbuilt until first needed with `lazy_trigrams`.
+    grams: OnceLock<Grams<S>>,
+    /// Each item's place in `items`, built on first need and dropped when
+    /// items move.
+    positions: OnceLock<HashMap<*const str, u32, S>>,
+    /// Items by the first letters of their words ("xsx" for "xbox series
+    /// x"), kept only when built with `acronyms`.
+    acronym_index: HashMap<String, Postings<S>, S>,
+    /// Ids of the distinct words in `word_sequences`.
+    vocabulary: HashMap<String, u32, S>,
+    /// Each item's words as ids, in order, kept only when built with
+    /// `phrases`.
+    word_sequences: HashMap<*const str, Vec<u32>, S>,
+    /// Results of short prefix queries under the matcher's own config,
+    /// with their totals, filled by `warm_up` and dropped when items are
+    /// added.
+    warm: HashMap<String, (Vec<Match<'a>>, usize), S>,
+    /// Languages of the items built through `new_tagged`.
+    languages: HashMap<*const str, Language, S>,
+    /// Some item is tagged `Language::Cjk`, so queries keep their CJK text.
+    cjk_items: bool,
+    /// Popularity of items by text, set through `set_weight`.
+    weights: HashMap<String, f32, S>,
+    /// Items by the phonetic key of each of their words.
+    #[cfg(feature = "phonetic")]
+    phonetic_index: HashMap<String, Postings<S>, S>,
     _phantom: PhantomData<&'a str>,
 }
 
-unsafe impl Send for QuickMatch<'_> {}
-unsafe impl Sync for QuickMatch<'_> {}
+/// The trigram side of the index.
+#[derive(Clone, Default)]
+struct Grams<S> {
+    index: HashMap<Gram, Postings<S>, S>,
+    /// The keys of `index`, kept only when built with `trigram_filter`.
+    filter: GramFilter,
+    /// Total entries across all trigram postings, for the auto budget.
+    postings: usize,
+    /// Where each (trigram, item) pair first occurs within its word, kept
+    /// only when built with `positional_trigrams`.
+    positions: HashMap<(Gram, *const str), u16, S>,
+    /// Chars 1, 2 and 4 of each 4-char window, kept only when built with
+    /// `skip_grams`. Swapping the window's 3rd and 4th chars turns its
+    /// trigram into this gram and this gram into its trigram, so query
+    /// trigrams are probed here and query skip-grams in `index`.
+    skip_grams: HashMap<Gram, Postings<S>, S>,
+}
+
+impl<S: BuildHasher + Default> Grams<S> {
+    /// Adds the grams of one word of `item`: over its bytes with
+    /// `byte_trigrams`, else over its `gram_units`.
+    fn add_word(&mut self, item: *const str, word: &str, config: &QuickMatchConfig) {
+        if config.byte_trigrams() {
+            self.add(item, word.as_bytes(), config);
+        } else {
+            self.add(item, &gram_units(word, config), config);
+        }
+    }
+
+    /// Adds the grams of one word of `item`, split into `units`.
+    fn add<U: Copy + Into<char>>(
+        &mut self,
+        item: *const str,
+        units: &[U],
+        config: &QuickMatchConfig,
+    ) {
+        for (pos, w) in units.windows(3).enumerate() {
+            let trigram = gram(w[0].into(), w[1].into(), w[2].into());
+            let new_key = config.trigram_filter() && !self.index.contains_key(&trigram);
+            if self.index.entry(trigram).or_default().insert(item) {
+                self.postings += 1;
+            }
+            // A full filter is rebuilt twice as large
+            if new_key && self.filter.is_full() {
+                self.filter = GramFilter::with_keys(self.index.keys().copied());
+            } else if new_key {
+                self.filter.insert(trigram);
+            }
+            if config.positional_trigrams() {
+                self.positions.entry((trigram, item)).or_insert(pos as u16);
+            }
+        }
+
+        if config.skip_grams() {
+            for w in units.windows(4) {
+                self.skip_grams
+                    .entry(gram(w[0].into(), w[1].into(), w[3].into()))
+                    .or_default()
+                    .insert(item);
+            }
+        }
+    }
+}
+
+/// A ranked result with its relevance: the share of query words the item
+/// covers, from `0.0` to `1.0`. Typo'd words count for their trigram overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Match<'a> {
+    pub item: &'a str,
+    pub score: f32,
+}
+
+/// A result with the query words it satisfied and the ones it didn't.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchReport<'a> {
+    pub item: &'a str,
+    pub score: f32,
+    pub matched: Vec<String>,
+    pub unmatched: Vec<String>,
+    /// Matched words reached only through typo matching, each with its
+    /// edit distance to the closest word of the item.
+    pub distances: Vec<(String, usize)>,
+}
+
+/// Three chars packed 21 bits apiece, which fits any `char`, so a gram key
+/// takes 8 bytes instead of 12 and hashes as one word.
+type Gram = u64;
+
+/// Fixed-point scale of similarities passed around as integer scores.
+const SIMILARITY_SCALE: f32 = 1_000_000.0;
+
+fn gram(a: char, b: char, c: char) -> Gram {
+    (a as u64) << 42 | (b as u64) << 21 | c as u64
+}
+
+/// Caller hook mapping a candidate's item and score to a new score.
+type Rerank<'r> = &'r dyn Fn(&str, f32) -> f32;
+
+/// Caller hook taking ranked results in order until it breaks.
+type Emit<'e> = RefCell<dyn FnMut(*const str, f32) -> ControlFlow<()> + 'e>;
+
+/// Results of a query, with how the search went.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Matches<'a> {
+    pub items: Vec<Match<'a>>,
+    /// The config's time budget ran out, `max_candidates` was reached, or
+    /// the query was cancelled, so these are the best results found before
+    /// that.
+    pub truncated: bool,
+    /// How many candidates qualified before the cut to `limit`, for
+    /// "showing 20 of 1,432". Counts only those ranked before the time
+    /// budget ran out.
+    pub total: usize,
+}
+
+/// Per-call extras threaded through a search.
+#[derive(Default)]
+struct Hooks<'h, S: Clone> {
+    rerank: Option<Rerank<'h>>,
+    /// Only items tagged with this language are returned.
+    language: Option<Language>,
+    /// Only items it accepts are returned.
+    filter: Option<&'h dyn Fn(&str) -> bool>,
+    /// Candidate pool of the query this one refines, to narrow instead of
+    /// intersecting postings.
+    within: Option<&'h [*const str]>,
+    /// Receives this query's candidate pool, for a refinement to narrow.
+    keep_pool: Option<&'h RefCell<Vec<*const str>>>,
+    /// Query words already resolved, e.g. by `matches_async` between its
+    /// yields; other words resolve as usual.
+    resolved: Option<&'h Resolved<'h, S>>,
+    /// Sees every candidate that qualifies, before the cut to `limit`.
+    visit: Option<&'h dyn Fn(&str)>,
+    /// Asked about ranked results in order, deciding which fill the page.
+    admit: Option<&'h dyn Fn(&str) -> bool>,
+    /// Handed the page in order as it is ranked, instead of collecting
+    /// it, until it breaks.
+    emit: Option<&'h Emit<'h>>,
+    /// Results handed to `emit` so far.
+    emitted: Cell<usize>,
+    cancel: Option<&'h CancelToken>,
+    /// Cancelling ranks what was gathered, flagged as truncated, instead
+    /// of giving up.
+    partial: bool,
+    deadline: Option<Instant>,
+    truncated: Cell<bool>,
+    /// Candidates that qualified for ranking, before the cut to `limit`.
+    total: Cell<usize>,
+    /// The query was already retried with nearest words, with `bk_tree`.
+    rewritten: Cell<bool>,
+    /// Words nothing else resolves resolve through their sound-alike key,
+    /// on the retry once typo matching found nothing.
+    sound_alike: Cell<bool>,
+    slow_query: Option<(Duration, &'h OnSlowQuery)>,
+    /// When the query and its current phase began, timed only for
+    /// `slow_query`.
+    started: Option<Instant>,
+    phase_start: Cell<Option<Instant>>,
+    /// Phase timings so far, kept only for `slow_query`.
+    phases: RefCell<Vec<(Phase, Duration)>>,
+}
+
+impl<'h, S: Clone + Default> Hooks<'h, S> {
+    /// Starts the clock on the config's time budget, if any, and on the
+    /// query and its first phase when there is a slow-query callback.
+    fn new(config: &'h QuickMatchConfig) -> Self {
+        let slow_query = config.slow_query();
+        let started = slow_query.is_some().then(Instant::now);
+        Self {
+            deadline: config.time_budget().map(|budget| Instant::now() + budget),
+            slow_query,
+            started,
+            phase_start: Cell::new(started),
+            ..Self::default()
+        }
+    }
+
+    /// Records `phase` as ending now, and starts timing the next one.
+    fn end_phase(&self, phase: Phase) {
+        let Some(start) = self.phase_start.get() else {
+            return;
+        };
+        let now = Instant::now();
+        self.phase_start.set(Some(now));
+        self.phases.borrow_mut().push((phase, now - start));
+    }
+
+    /// Hands the query to the slow-query callback if it took too long.
+    fn check_slow(&self, query: &str, config: &QuickMatchConfig) {
+        let (Some((threshold, on_slow)), Some(started)) = (self.slow_query, self.started) else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        if elapsed >= threshold {
+            on_slow(&SlowQuery {
+                query: &normalize(query, config, config.cjk_bigrams()),
+                elapsed,
+                phases: self.phases.take(),
+            });
+        }
+    }
+
+    /// Whether results can come from the `warm_up` cache: no hook needs
+    /// to see or reorder the full candidate pool.
+    fn cacheable(&self) -> bool {
+        self.rerank.is_none()
+            && self.language.is_none()
+            && self.filter.is_none()
+            && self.visit.is_none()
+            && self.admit.is_none()
+    }
+
+    fn admits(&self, item: &str) -> bool {
+        self.admit.is_none_or(|admit| admit(item))
+    }
+
+    /// Passes ranked results on: to `emit` when set, else into `results`.
+    /// Returns how many were passed, or `None` once `emit` breaks.
+    fn deliver<'a>(
+        &self,
+        ranked: impl IntoIterator<Item = Match<'a>>,
+        results: &mut Vec<Match<'a>>,
+    ) -> Option<usize> {
+        let Some(emit) = self.emit else {
+            let before = results.len();
+            results.extend(ranked);
+            return Some(results.len() - before);
+        };
+        let mut passed = 0;
+        for m in ranked {
+            passed += 1;
+            self.emitted.set(self.emitted.get() + 1);
+            if (emit.borrow_mut())(m.item, m.score).is_break() {
+                return None;
+            }
+        }
+        Some(passed)
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Whether to stop for a cancel: `Some(true)` to rank what was
+    /// gathered, `Some(false)` to give up, `None` to go on.
+    fn stop(&self) -> Option<bool> {
+        if !self.cancelled() {
+            return None;
+        }
+        self.truncated.set(self.partial);
+        Some(self.partial)
+    }
+
+    /// Whether the deadline has passed, flagging the results as truncated.
+    fn out_of_time(&self) -> bool {
+        let out = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if out {
+            self.truncated.set(true);
+        }
+        out
+    }
+}
+
+/// Buffers a search fills and empties again, kept across the queries of a
+/// batch so their allocations are reused.
+#[derive(Default)]
+struct Scratch {
+    scores: FxHashMap<*const str, usize>,
+    visited: FxHashSet<Gram>,
+    visited_skip_grams: FxHashSet<Gram>,
+}
+
+/// Items holding a key, by address.
+type Postings<S> = HashSet<*const str, S>;
+
+/// Postings of query words resolved ahead of a search, `None` for words
+/// that resolve to nothing.
+struct Resolved<'q, S: Clone>(FxHashMap<String, Option<Cow<'q, Postings<S>>>>);
+
+// The addresses are of items the matcher borrows, which are `Sync`
+unsafe impl<S: Clone + Send + Sync> Send for Resolved<'_, S> {}
+
+unsafe impl<S: Send> Send for QuickMatch<'_, S> {}
+unsafe impl<S: Sync> Sync for QuickMatch<'_, S> {}
+
+impl<'a> QuickMatch<'a> {
+    /// Expect the items to be pre-formatted (lowercase)
+    pub fn new(items: &[&'a str]) -> Self {
+        Self::new_with(items, QuickMatchConfig::default())
+    }
+
+    /// Like `new`, borrowing from anything holding the item texts, e.g.
+    /// `&[String]`, without collecting a `Vec<&str>` first.
+    pub fn from_items<T: AsRef<str> + ?Sized + 'a>(items: impl IntoIterator<Item = &'a T>) -> Self {
+        Self::from_items_with(items, QuickMatchConfig::default())
+    }
+
+    pub fn from_items_with<T: AsRef<str> + ?Sized + 'a>(
+        items: impl IntoIterator<Item = &'a T>,
+        config: QuickMatchConfig,
+    ) -> Self {
+        let mut qm = Self::new_with(&[], config);
+        qm.extend(items);
+        qm
+    }
+
+    /// Like `new_with`, tagging each item with its language, which picks
+    /// the extra analysis its words get and lets `matches_in` search one
+    /// language only.
+    pub fn new_tagged(items: &[(&'a str, Language)], config: QuickMatchConfig) -> Self {
+        let mut qm = Self::new_with(&[], config);
+        let sep = sep_table(&qm.config);
+        for &(item, language) in items {
+            qm.index(item, Some(language), &sep);
+        }
+        qm
+    }
+
+    /// Expect the items to be pre-formatted (lowercase)
+    pub fn new_with(items: &[&'a str], config: QuickMatchConfig) -> Self {
+        Self::new_with_hasher(items, config)
+    }
+}
+
+impl<'a, S: BuildHasher + Default + Clone> QuickMatch<'a, S> {
+    /// Like `new_with`, with maps hashing through `S`, e.g.
+    /// `QuickMatch::<RandomState>::new_with_hasher(..)`.
+    pub fn new_with_hasher(items: &[&'a str], config: QuickMatchConfig) -> Self {
+        let grams = if config.lazy_trigrams() && !config.words_only() {
+            OnceLock::new()
+        } else {
+            OnceLock::from(Grams::default())
+        };
+        let mut qm = Self {
+            items: vec![],
+            seen: HashSet::default(),
+            duplicates: 0,
+            max_query_len: 6,
+            max_word_len: 4,
+            max_word_count: 2,
+            word_index: HashMap::default(),
+            word_ids: HashMap::default(),
+            sorted_words: BTreeSet::new(),
+            word_counts: BTreeMap::new(),
+            bk_tree: BkTree::default(),
+            grams,
+            positions: OnceLock::new(),
+            acronym_index: HashMap::default(),
+            vocabulary: HashMap::default(),
+            word_sequences: HashMap::default(),
+            warm: HashMap::default(),
+            languages: HashMap::default(),
+            cjk_items: false,
+            weights: HashMap::default(),
+            #[cfg(feature = "phonetic")]
+            phonetic_index: HashMap::default(),
+            config,
+            _phantom: PhantomData,
+        };
+        qm.extend(items.iter().copied());
+        qm
+    }
+
+    /// Adds one item to the word and trigram indexes, widening the query
+    /// guards so the new item stays reachable.
+    fn index(&mut self, item: &'a str, language: Option<Language>, sep: &[bool; 256]) {
+        if self.config.dedup() && !self.seen.insert(item) {
+            self.duplicates += 1;
+            return;
+        }
+        if let Some(language) = language {
+            self.languages.insert(item, language);
+            self.cjk_items |= language == Language::Cjk;
+        }
+        self.warm.clear();
+        if let Some(positions) = self.positions.get_mut() {
+            positions.entry(item).or_insert(self.items.len() as u32);
+        }
+        self.items.push(item);
+        let text = self.item_text(item);
+        let item_words: Vec<&str> = words(&text, sep).collect();
+        self.max_query_len = self.max_query_len.max(item.len() + 6);
+        let word_count = if self.config.split_digits() {
+            item_words.iter().map(|w| digit_runs(w).count()).sum()
+        } else {
+            item_words.len()
+        };
+        self.max_word_count = self.max_word_count.max(word_count + 2);
+
+        for (i, word) in item_words.iter().enumerate() {
+            self.max_word_len = self.max_word_len.max(word.len() + 4);
+            if !item_words[..i].contains(word) {
+                let count = self.word_counts.entry(word.to_string()).or_default();
+                *count += 1;
+                if *count == 1 && self.config.bk_tree() {
+                    self.bk_tree.insert(word);
+                }
+            }
+
+            self.index_prefixes(word, 1, item);
+
+            if !self.config.words_only()
+                && let Some(grams) = self.grams.get_mut()
+            {
+                grams.add_word(item, word, &self.config);
+            }
+
+            if self.config.split_digits() && digit_runs(word).nth(1).is_some() {
+                for run in digit_runs(word) {
+                    self.index_prefixes(run, 1, item);
+                }
+            }
+
+            if language == Some(Language::English)
+                && let Some(singular) = language::singular(word)
+            {
+                self.index_prefixes(&singular, singular.len(), item);
+            }
+
+            if self.config.cjk_bigrams() || language == Some(Language::Cjk) {
+                for term in cjk::index_terms(word) {
+                    self.post_word(term, item);
+                }
+            }
+
+            #[cfg(feature = "phonetic")]
+            if self.config.phonetic()
+                && let Some(key) = phonetic::phonetic_key(word)
+            {
+                self.phonetic_index.entry(key).or_default().insert(item);
+            }
+        }
+
+        if self.config.phrases() {
+            let sequence = item_words.iter().map(|w| self.word_id(w)).collect();
+            self.word_sequences.insert(item, sequence);
+        }
+
+        if self.config.acronyms() && item_words.len() >= 2 {
+            let acronym: String = item_words.iter().filter_map(|w| w.chars().next()).collect();
+            self.acronym_index.entry(acronym).or_default().insert(item);
+        }
+
+        for pair in item_words.windows(2) {
+            let compound = format!("{}{}", pair[0], pair[1]);
+            // A joined-word query ("hashrate") can be longer than any
+            // single word. Capping at the longest index key keeps the
+            // DDoS guard data-bounded while still letting it match.
+            self.max_word_len = self.max_word_len.max(compound.len() + 4);
+            self.index_prefixes(&compound, pair[0].len() + 1, item);
+        }
+    }
+
+    /// Adds `item` under the prefixes of `word` at least `from` bytes long,
+    /// or with `prefix_expansion` under the whole word only.
+    fn index_prefixes(&mut self, word: &str, from: usize, item: &'a str) {
+        if self.config.prefix_expansion().is_some() {
+            if !self.word_index.contains_key(word) {
+                self.sorted_words.insert(word.to_string());
+            }
+            self.post_word(word, item);
+            return;
+        }
+        // Prefixes end on char boundaries, so non-ASCII items don't panic
+        for len in (from..=word.len()).filter(|&len| word.is_char_boundary(len)) {
+            self.post_word(&word[..len], item);
+        }
+    }
+
+    /// Adds `item`, the latest of `items`, under `word` in `word_index`,
+    /// and with `sorted_postings` its id to the end of the word's list.
+    fn post_word(&mut self, word: &str, item: &'a str) {
+        let added = self
+            .word_index
+            .entry(word.to_string())
+            .or_default()
+            .insert(item);
+        if added && self.config.sorted_postings() {
+            let id = self.items.len() as u32 - 1;
+            self.word_ids.entry(word.to_string()).or_default().push(id);
+        }
+    }
+
+    /// Rebuilds `word_ids` from `word_index` once items have moved.
+    fn rebuild_word_ids(&mut self) {
+        if !self.config.sorted_postings() {
+            return;
+        }
+        let ids: FxHashMap<*const str, u32> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(id, &item)| (item as *const str, id as u32))
+            .collect();
+        self.word_ids = self
+            .word_index
+            .iter()
+            .map(|(word, items)| {
+                let mut list: Vec<u32> = items.iter().map(|item| ids[item]).collect();
+                list.sort_unstable();
+                (word.clone(), list)
+            })
+            .collect();
+    }
+
+    /// The trigram side of the index, built now if `lazy_trigrams`
+    /// deferred it.
+    fn grams(&self) -> &Grams<S> {
+        self.grams.get_or_init(|| {
+            let mut grams = Grams::default();
+            let sep = sep_table(&self.config);
+            for &item in &self.items {
+                let text = self.item_text(item);
+                for word in words(&text, &sep) {
+                    grams.add_word(item, word, &self.config);
+                }
+            }
+            grams
+        })
+    }
+
+    /// Where `item` was given among the items.
+    fn position(&self, item: *const str) -> u32 {
+        let positions = self.positions.get_or_init(|| {
+            let mut positions = HashMap::default();
+            for (i, &item) in self.items.iter().enumerate() {
+                positions.entry(item as *const str).or_insert(i as u32);
+            }
+            positions
+        });
+        positions.get(&item).copied().unwrap_or(u32::MAX)
+    }
+
+    /// Builds the trigram index now if `lazy_trigrams` deferred it, e.g.
+    /// from a background thread while exact queries are already served.
+    pub fn prepare_trigrams(&self) {
+        self.grams();
+    }
+
+    /// Combines two built matchers into one without re-tokenizing, e.g.
+    /// shards indexed in parallel. `other` should have been built with the
+    /// same config as `self`, whose config the result keeps. With `dedup`,
+    /// items of `other` repeating a text already in `self` are dropped.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.warm.clear();
+        self.positions = OnceLock::new();
+        let mut dropped: FxHashSet<*const str> = FxHashSet::default();
+        // Dropped items were counted in `other`'s word counts
+        let mut word_counts = other.word_counts;
+        let sep = sep_table(&self.config);
+        for item in other.items {
+            if self.config.dedup() && !self.seen.insert(item) {
+                self.duplicates += 1;
+                dropped.insert(item);
+                self.uncount_words(&mut word_counts, item, &sep);
+            } else {
+                self.items.push(item);
+            }
+        }
+        self.duplicates += other.duplicates;
+        self.max_word_count = self.max_word_count.max(other.max_word_count);
+        self.max_word_len = self.max_word_len.max(other.max_word_len);
+        self.max_query_len = self.max_query_len.max(other.max_query_len);
+
+        merge_postings(&mut self.word_index, other.word_index, &dropped);
+        self.sorted_words.extend(other.sorted_words);
+        for (word, count) in word_counts {
+            if self.config.bk_tree() && !self.word_counts.contains_key(&word) {
+                self.bk_tree.insert(&word);
+            }
+            *self.word_counts.entry(word).or_default() += count;
+        }
+        match (self.grams.get_mut(), other.grams.into_inner()) {
+            (Some(grams), Some(other)) => {
+                grams.postings += merge_postings(&mut grams.index, other.index, &dropped);
+                if self.config.trigram_filter() {
+                    grams.filter = GramFilter::with_keys(grams.index.keys().copied());
+                }
+                for (key, pos) in other.positions {
+                    if !dropped.contains(&key.1) {
+                        grams.positions.entry(key).or_insert(pos);
+                    }
+                }
+                merge_postings(&mut grams.skip_grams, other.skip_grams, &dropped);
+            }
+            // Either side still lazy: build the whole on first need
+            _ => self.grams = OnceLock::new(),
+        }
+        merge_postings(&mut self.acronym_index, other.acronym_index, &dropped);
+        let ids: FxHashMap<u32, u32> = other
+            .vocabulary
+            .into_iter()
+            .map(|(word, id)| (id, self.word_id(&word)))
+            .collect();
+        for (item, sequence) in other.word_sequences {
+            if !dropped.contains(&item) {
+                let sequence = sequence.iter().map(|id| ids[id]).collect();
+                self.word_sequences.insert(item, sequence);
+            }
+        }
+        self.languages.extend(
+            other
+                .languages
+                .into_iter()
+                .filter(|(item, _)| !dropped.contains(item)),
+        );
+        self.cjk_items |= other.cjk_items;
+        for (item, weight) in other.weights {
+            self.weights.entry(item).or_insert(weight);
+        }
+        #[cfg(feature = "phonetic")]
+        merge_postings(&mut self.phonetic_index, other.phonetic_index, &dropped);
+        self.rebuild_word_ids();
+        self
+    }
+
+    /// Takes one off the count of each distinct word of `item`, removing
+    /// words it leaves at zero.
+    fn uncount_words(
+        &self,
+        word_counts: &mut BTreeMap<String, usize>,
+        item: &str,
+        sep: &[bool; 256],
+    ) {
+        let text = self.item_text(item);
+        let item_words: Vec<&str> = words(&text, sep).collect();
+        for (i, word) in item_words.iter().enumerate() {
+            if item_words[..i].contains(word) {
+                continue;
+            }
+            if let Some(count) = word_counts.get_mut(*word) {
+                *count -= 1;
+                if *count == 0 {
+                    word_counts.remove(*word);
+                }
+            }
+        }
+    }
+
+    /// The item as split into words: through the config's normalizer, or
+    /// with `nfkc` folded, its apostrophes and hyphens handled per
+    /// `joiners`, its emoji and symbols per `symbols`, its units joined to
+    /// their numbers with `units`, and then, past ASCII folding, its case
+    /// folded.
+    fn item_text<'t>(&self, item: &'t str) -> Cow<'t, str> {
+        if let Some(normalizer) = self.config.normalizer() {
+            return normalizer(item);
+        }
+        let text = if self.config.nfkc() {
+            nfkc::fold(item)
+        } else {
+            Cow::Borrowed(item)
+        };
+        let text = match self.config.joiners() {
+            Some(joiners) => then(text, |text| joiners::apply(text, joiners)),
+            None => text,
+        };
+        let text = then(text, |text| symbols::apply(text, self.config.symbols()));
+        let text = then(text, |text| split_marks(text, &self.config));
+        let text = if self.config.units() {
+            then(text, units::join)
+        } else {
+            text
+        };
+        match self.config.case_folding() {
+            CaseFolding::Ascii => text,
+            folding => then(text, |text| casefold::fold(text, folding)),
+        }
+    }
+
+    /// The item's words, sorted, and the set of their trigrams, for
+    /// spotting near-duplicates.
+    fn fingerprint(&self, item: &str) -> (Vec<String>, FxHashSet<Gram>) {
+        let mut words = self.words_of(item);
+        let grams = words.iter().flat_map(|word| self.grams_of(word)).collect();
+        words.sort_unstable();
+        (words, grams)
+    }
+
+    fn grams_of(&self, word: &str) -> FxHashSet<Gram> {
+        self.gram_units(word)
+            .windows(3)
+            .map(|w| gram(w[0], w[1], w[2]))
+            .collect()
+    }
+
+    /// Counts of the words and trigrams of `words`, for `Scoring::Cosine`.
+    /// Words are keyed by their hash with the top bit set, which no packed
+    /// trigram has.
+    fn term_counts<'w>(&self, words: impl Iterator<Item = &'w str>) -> FxHashMap<u64, f32> {
+        let mut counts: FxHashMap<u64, f32> = FxHashMap::default();
+        for word in words {
+            *counts
+                .entry(FxBuildHasher.hash_one(word) | 1 << 63)
+                .or_default() += 1.0;
+            let units = self.gram_units(word);
+            for w in units.windows(3) {
+                *counts.entry(gram(w[0], w[1], w[2])).or_default() += 1.0;
+            }
+        }
+        counts
+    }
+
+    /// `Scoring::Jaccard` of an item: for each of `query_grams`, the best
+    /// Jaccard similarity to an item word, averaged.
+    fn word_similarity(&self, item: &str, query_grams: &[FxHashSet<Gram>]) -> f32 {
+        let item_grams: Vec<FxHashSet<Gram>> = self
+            .words_of(item)
+            .iter()
+            .map(|word| self.grams_of(word))
+            .collect();
+        let total: f32 = query_grams
+            .iter()
+            .map(|grams| {
+                item_grams
+                    .iter()
+                    .map(|other| jaccard(grams, other))
+                    .fold(0.0, f32::max)
+            })
+            .sum();
+        total / query_grams.len().max(1) as f32
+    }
+
+    /// Whether queries keep and split CJK text: under `cjk_bigrams`, or
+    /// once some item is tagged `Language::Cjk`.
+    fn cjk(&self, config: &QuickMatchConfig) -> bool {
+        config.cjk_bigrams() || self.cjk_items
+    }
+
+    /// Rebuilds every index from the stored items under `config`, e.g. new
+    /// separators, so the caller needs not keep the original slice around.
+    /// Language tags and weights carry over.
+    pub fn reindex_with(&mut self, config: QuickMatchConfig) {
+        let items = mem::take(&mut self.items);
+        let languages = mem::take(&mut self.languages);
+        let weights = mem::take(&mut self.weights);
+        *self = Self::new_with_hasher(&[], config);
+        self.weights = weights;
+        let sep = sep_table(&self.config);
+        for item in items {
+            let language = languages.get(&(item as *const str)).copied();
+            self.index(item, language, &sep);
+        }
+    }
+
+    /// Removes the items `keep` rejects, in one pass over the indexes,
+    /// dropping keys no item is left under. The query guards stay as
+    /// wide as before.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        let dropped: FxHashSet<*const str> = self
+            .items
+            .iter()
+            .filter(|item| !keep(item))
+            .map(|&item| item as *const str)
+            .collect();
+        if dropped.is_empty() {
+            return;
+        }
+        self.warm.clear();
+
+        let sep = sep_table(&self.config);
+        let (kept, removed): (Vec<&'a str>, Vec<&'a str>) = self
+            .items
+            .iter()
+            .partition(|&&item| !dropped.contains(&(item as *const str)));
+        self.items = kept;
+        self.positions = OnceLock::new();
+        let mut word_counts = mem::take(&mut self.word_counts);
+        for item in removed {
+            self.seen.remove(item);
+            self.uncount_words(&mut word_counts, item, &sep);
+        }
+        self.word_counts = word_counts;
+
+        if self.config.bk_tree() {
+            self.bk_tree = BkTree::default();
+            for word in self.word_counts.keys() {
+                self.bk_tree.insert(word);
+            }
+        }
+        prune_postings(&mut self.word_index, &dropped);
+        self.rebuild_word_ids();
+        if !self.sorted_words.is_empty() {
+            self.sorted_words
+                .retain(|word| self.word_index.contains_key(word));
+        }
+        if let Some(grams) = self.grams.get_mut() {
+            grams.postings -= prune_postings(&mut grams.index, &dropped);
+            grams
+                .positions
+                .retain(|(_, item), _| !dropped.contains(item));
+            prune_postings(&mut grams.skip_grams, &dropped);
+        }
+        prune_postings(&mut self.acronym_index, &dropped);
+        self.word_sequences
+            .retain(|item, _| !dropped.contains(item));
+        self.languages.retain(|item, _| !dropped.contains(item));
+        #[cfg(feature = "phonetic")]
+        prune_postings(&mut self.phonetic_index, &dropped);
+    }
+
+    /// Shrinks every map, set and list to fit what it holds, rehashing
+    /// into right-sized tables; worth it once a large build is done, as
+    /// growth by doubling can leave about half of each table empty.
+    pub fn optimize(&mut self) {
+        self.items.shrink_to_fit();
+        self.seen.shrink_to_fit();
+        shrink_postings(&mut self.word_index);
+        for ids in self.word_ids.values_mut() {
+            ids.shrink_to_fit();
+        }
+        self.word_ids.shrink_to_fit();
+        self.bk_tree.shrink_to_fit();
+        if let Some(grams) = self.grams.get_mut() {
+            shrink_postings(&mut grams.index);
+            grams.positions.shrink_to_fit();
+            shrink_postings(&mut grams.skip_grams);
+        }
+        shrink_postings(&mut self.acronym_index);
+        self.vocabulary.shrink_to_fit();
+        for sequence in self.word_sequences.values_mut() {
+            sequence.shrink_to_fit();
+        }
+        self.word_sequences.shrink_to_fit();
+        self.warm.shrink_to_fit();
+        self.languages.shrink_to_fit();
+        self.weights.shrink_to_fit();
+        #[cfg(feature = "phonetic")]
+        shrink_postings(&mut self.phonetic_index);
+    }
+
+    fn word_id(&mut self, word: &str) -> u32 {
+        let next = self.vocabulary.len() as u32;
+        *self.vocabulary.entry(word.to_string()).or_insert(next)
+    }
+
+    fn gram_units(&self, word: &str) -> Vec<char> {
+        gram_units(word, &self.config)
+    }
+
+    /// A query word in the units item grams were windowed over: its bytes
+    /// with `byte_trigrams`, else its `gram_units`.
+    fn query_units(&self, word: &str) -> Vec<char> {
+        if self.config.byte_trigrams() {
+            word.bytes().map(char::from).collect()
+        } else {
+            self.gram_units(word)
+        }
+    }
+
+    /// Precomputes the results of the 1 to 3 char prefixes of the items'
+    /// words, the first keystrokes of most searches, so queries for them
+    /// under the matcher's own config are answered from memory. The most
+    /// common prefixes go first, until `max_results` results in total are
+    /// stored; results cut short by the time budget are left out. Adding
+    /// items drops the cache. Returns how many prefixes
+    /// were cached.
+    pub fn warm_up(&mut self, max_results: usize) -> usize {
+        self.warm.clear();
+        let sep = sep_table(&self.config);
+        // The prefixes of the words as indexed
+        let mut counts: FxHashMap<String, usize> = FxHashMap::default();
+        for item in &self.items {
+            let text = self.item_text(item);
+            for word in words(&text, &sep) {
+                for len in (1..=3.min(word.len())).filter(|&len| word.is_char_boundary(len)) {
+                    if let Some(count) = counts.get_mut(&word[..len]) {
+                        *count += 1;
+                    } else {
+                        counts.insert(word[..len].to_string(), 1);
+                    }
+                }
+            }
+        }
+        let mut prefixes: Vec<(String, usize)> = counts.into_iter().collect();
+        prefixes.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut warm = HashMap::default();
+        let mut stored = 0;
+        for (prefix, _) in prefixes {
+            let Matches {
+                items: results,
+                truncated,
+                total,
+            } = self.matches_detailed(&prefix);
+            if truncated {
+                continue;
+            }
+            if stored + results.len() > max_results {
+                break;
+            }
+            stored += results.len();
+            warm.insert(
+                normalize(&prefix, &self.config, self.cjk(&self.config)),
+                (results, total),
+            );
+        }
+        self.warm = warm;
+        self.warm.len()
+    }
+
+    /// Item words within `max_distance` edits of `word`, closest first (ties
+    /// in word order). Walks the BK-tree when built with `bk_tree`, or
+    /// else the whole vocabulary.
+    pub fn nearest_words(&self, word: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        let mut nearest = if self.bk_tree.is_empty() {
+            distance::LevenshteinAutomaton::new(word, max_distance)
+                .matches(self.word_counts.keys().map(String::as_str))
+                .into_iter()
+                .map(|w| (w, distance::edit_distance(word, w)))
+                .collect()
+        } else {
+            self.bk_tree.find(word, max_distance)
+        };
+        nearest.sort_unstable_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+        nearest
+    }
+
+    /// Items whose trigram sets have a Jaccard similarity of at least
+    /// `threshold` with that of `item`, most similar first, leaving out
+    /// items with the same text as `item`. Compares only the items sharing
+    /// a trigram with it.
+    pub fn near_duplicates(&self, item: &str, threshold: f32) -> Vec<(&'a str, f32)> {
+        let (_, grams) = self.fingerprint(item);
+        let candidates: FxHashSet<*const str> = grams
+            .iter()
+            .filter_map(|gram| self.grams().index.get(gram))
+            .flatten()
+            .copied()
+            .collect();
+        let mut found: Vec<(&'a str, f32)> = self
+            .items
+            .iter()
+            .filter(|&&other| other != item && candidates.contains(&(other as *const str)))
+            .map(|&other| (other, jaccard(&grams, &self.fingerprint(other).1)))
+            .filter(|&(_, similarity)| similarity >= threshold)
+            .collect();
+        found.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+        found
+    }
+
+    /// Every pair of items whose trigram sets have a Jaccard similarity of
+    /// at least `threshold`, most similar first. Candidate pairs come from
+    /// MinHash signatures bucketed by band, so pairs just above a low
+    /// `threshold` can be missed; above 0.75 almost none are.
+    pub fn all_near_duplicate_pairs(&self, threshold: f32) -> Vec<(&'a str, &'a str, f32)> {
+        let grams: Vec<FxHashSet<Gram>> = self
+            .items
+            .iter()
+            .map(|item| self.fingerprint(item).1)
+            .collect();
+        let mut buckets: FxHashMap<(usize, u64), Vec<usize>> = FxHashMap::default();
+        for (i, set) in grams.iter().enumerate().filter(|(_, set)| !set.is_empty()) {
+            let signature = minhash::signature(set.iter().copied());
+            for key in minhash::band_keys(&signature) {
+                buckets.entry(key).or_default().push(i);
+            }
+        }
+        let mut seen: FxHashSet<(usize, usize)> = FxHashSet::default();
+        let mut pairs = vec![];
+        for bucket in buckets.values() {
+            for (n, &i) in bucket.iter().enumerate() {
+                for &j in &bucket[n + 1..] {
+                    if !seen.insert((i, j)) {
+                        continue;
+                    }
+                    let similarity = jaccard(&grams[i], &grams[j]);
+                    if similarity >= threshold {
+                        pairs.push((self.items[i], self.items[j], similarity));
+                    }
+                }
+            }
+        }
+        pairs.sort_unstable_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(b.0)).then(a.1.cmp(b.1)));
+        pairs
+    }
+
+    /// Indexed words (not items) that `prefix` is the start of, for
+    /// completing the word being typed, most common first (by the number
+    /// of items holding them), up to the config's limit.
+    pub fn complete(&self, prefix: &str) -> Vec<&str> {
+        let prefix = normalize(prefix, &self.config, self.cjk(&self.config));
+        if prefix.is_empty() {
+            return vec![];
+        }
+        let mut words: Vec<(&str, usize)> = self
+            .word_counts
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(|(word, _)| word.starts_with(&prefix))
+            .map(|(word, &count)| (word.as_str(), count))
+            .collect();
+        words.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        words.truncate(self.config.limit());
+        words.into_iter().map(|(word, _)| word).collect()
+    }
+
+    /// Sets the popularity of every item with this text, e.g. bumped each
+    /// time a user picks it. Ranking adds `weight_blend` times the weight
+    /// to each result's relevance; a weight of 0.0 clears it. Drops the
+    /// `warm_up` cache.
+    pub fn set_weight(&mut self, item: &str, weight: f32) {
+        self.warm.clear();
+        if weight == 0.0 || !weight.is_finite() {
+            self.weights.remove(item);
+        } else {
+            self.weights.insert(item.to_string(), weight);
+        }
+    }
+
+    pub fn weight(&self, item: &str) -> f32 {
+        self.weights.get(item).copied().unwrap_or(0.0)
+    }
+
+    pub fn config(&self) -> &QuickMatchConfig {
+        &self.config
+    }
+
+    /// Number of indexed items, repeats included unless built with `dedup`.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn contains(&self, item: &str) -> bool {
+        if self.config.dedup() {
+            self.seen.contains(item)
+        } else {
+            self.items.contains(&item)
+        }
+    }
+
+    /// The indexed items, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.items.iter().copied()
+    }
+
+    /// How many items were skipped for repeating an earlier item's text,
+    /// when built with `dedup`.
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// The words `item` is (or would be) indexed under, in order, after
+    /// the config's normalizer or NFKC folding.
+    pub fn words_of(&self, item: &str) -> Vec<String> {
+        let sep = sep_table(&self.config);
+        words(&self.item_text(item), &sep)
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The distinct trigrams of `item`'s words, in order, as probed by
+    /// typo matching.
+    pub fn trigrams_of(&self, item: &str) -> Vec<String> {
+        let mut trigrams: Vec<String> = vec![];
+        for word in self.words_of(item) {
+            for w in self.gram_units(&word).windows(3) {
+                let trigram = String::from_iter(w);
+                if !trigrams.contains(&trigram) {
+                    trigrams.push(trigram);
+                }
+            }
+        }
+        trigrams
+    }
+
+    /// How many items the word index holds under `word`: those with a
+    /// word it starts, or with `prefix_expansion` a word it equals.
+    pub fn postings_len(&self, word: &str) -> usize {
+        self.word_index.get(word).map_or(0, |items| items.len())
+    }
+
+    /// Trigram budget picked from the corpus, used when the config asks for
+    /// it: each probe walks a posting list, so the longer the average list,
+    /// the fewer probes. From 15 on tiny corpora down to 3 on huge ones.
+    pub fn auto_trigram_budget(&self) -> usize {
+        let grams = self.grams();
+        let avg = grams.postings / grams.index.len().max(1);
+        (16 - 2 * avg.max(1).ilog2() as usize).clamp(3, 15)
+    }
+
+    pub fn matches(&self, query: &str) -> Vec<&'a str> {
+        self.matches_with(query, &self.config)
+    }
+
+    pub fn matches_with(&self, query: &str, config: &QuickMatchConfig) -> Vec<&'a str> {
+        self.matches_scored_with(query, config)
+            .into_iter()
+            .map(|m| m.item)
+            .collect()
+    }
+
+    pub fn try_matches(&self, query: &str) -> Result<Vec<&'a str>, QueryError> {
+        self.try_matches_with(query, &self.config)
+    }
+
+    /// Like `matches_with`, with an error instead of an empty result when
+    /// the query is rejected before searching.
+    pub fn try_matches_with(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+    ) -> Result<Vec<&'a str>, QueryError> {
+        self.check_query(query, config)?;
+        Ok(self.matches_with(query, config))
+    }
+
+    /// The guards of `run_search`, naming the one a query trips.
+    fn check_query(&self, query: &str, config: &QuickMatchConfig) -> Result<(), QueryError> {
+        let query = normalize(query, config, self.cjk(config));
+        let max = config.max_query_len().unwrap_or(self.max_query_len);
+        if query.is_empty() {
+            return Err(QueryError::Empty);
+        }
+        if query.len() > max {
+            return Err(QueryError::TooLong {
+                len: query.len(),
+                max,
+            });
+        }
+        let query = self.unquote(query, config);
+        let sep = sep_table(config);
+        let mut query_words = split_query(&query, &sep, self.cjk(config), config);
+        if query_words.is_empty() {
+            return Err(QueryError::NoWords);
+        }
+        query_words.retain(|w| w.len() <= self.max_word_len);
+        if query_words.is_empty() {
+            return Err(QueryError::WordsTooLong {
+                max: self.max_word_len,
+            });
+        }
+        if query_words.len() > self.max_word_count {
+            return Err(QueryError::TooManyWords {
+                count: query_words.len(),
+                max: self.max_word_count,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn matches_scored(&self, query: &str) -> Vec<Match<'a>> {
+        self.matches_scored_with(query, &self.config)
+    }
+
+    pub fn matches_scored_with(&self, query: &str, config: &QuickMatchConfig) -> Vec<Match<'a>> {
+        self.search(query, config, &Hooks::new(config), &mut Scratch::default())
+            .unwrap_or_default()
+    }
+
+    pub fn matches_detailed(&self, query: &str) -> Matches<'a> {
+        self.matches_detailed_with(query, &self.config)
+    }
+
+    /// Like `matches_scored_with`, also telling how many candidates
+    /// qualified in total and whether the config's time budget cut the
+    /// search short.
+    pub fn matches_detailed_with(&self, query: &str, config: &QuickMatchConfig) -> Matches<'a> {
+        let hooks = Hooks::new(config);
+        let items = self
+            .search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default();
+        Matches {
+            items,
+            truncated: hooks.truncated.get(),
+            total: hooks.total.get(),
+        }
+    }
+
+    pub fn matches_visit<B>(
+        &self,
+        query: &str,
+        visit: impl FnMut(&'a str, f32) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        self.matches_visit_with(query, &self.config, visit)
+    }
+
+    /// Hands the results to `visit` best first, with their scores, as they
+    /// come out of ranking, until it breaks, returning its `Break` value.
+    /// No page is collected, and breaking skips sorting the rest of the
+    /// candidates. Cached results and `input_order`, which reorders the
+    /// full page, are collected first.
+    pub fn matches_visit_with<B>(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        mut visit: impl FnMut(&'a str, f32) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        let mut broke = None;
+        let emit = RefCell::new(|item: *const str, score| {
+            match visit(unsafe { &*item as &'a str }, score) {
+                ControlFlow::Continue(()) => ControlFlow::Continue(()),
+                ControlFlow::Break(b) => {
+                    broke = Some(b);
+                    ControlFlow::Break(())
+                }
+            }
+        });
+        let hooks = Hooks {
+            emit: if config.input_order() {
+                None
+            } else {
+                Some(&emit)
+            },
+            ..Hooks::new(config)
+        };
+        let collected = self
+            .search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default();
+        let mut emit = emit.into_inner();
+        for m in collected {
+            if emit(m.item, m.score).is_break() {
+                break;
+            }
+        }
+        broke.map_or(ControlFlow::Continue(()), ControlFlow::Break)
+    }
+
+    pub fn matches_in(&self, query: &str, language: Language) -> Vec<&'a str> {
+        self.matches_in_with(query, &self.config, language)
+    }
+
+    /// Like `matches_with`, returning only items tagged with `language`.
+    pub fn matches_in_with(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        language: Language,
+    ) -> Vec<&'a str> {
+        let hooks = Hooks {
+            language: Some(language),
+            ..Hooks::new(config)
+        };
+        self.search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.item)
+            .collect()
+    }
+
+    pub fn matches_parsed(&self, query: &ParsedQuery) -> Vec<&'a str> {
+        self.matches_parsed_with(query, &self.config)
+    }
+
+    /// Searches the words and phrase words of `query`, keeping only items
+    /// that hold each phrase's words in a row and no word starting with an
+    /// excluded one.
+    pub fn matches_parsed_with(
+        &self,
+        query: &ParsedQuery,
+        config: &QuickMatchConfig,
+    ) -> Vec<&'a str> {
+        let sep = sep_table(config);
+        let cjk = self.cjk(config);
+        let split = |text: &str| -> Vec<String> {
+            words(&normalize(text, config, cjk), &sep)
+                .map(str::to_string)
+                .collect()
+        };
+        // A phrase of separators alone, e.g. `"--"`, asks for nothing
+        let phrases: Vec<Vec<String>> = query
+            .phrases
+            .iter()
+            .map(|p| split(p))
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        let excluded: Vec<String> = query.excluded.iter().flat_map(|e| split(e)).collect();
+
+        let item_sep = sep_table(&self.config);
+        let filter = |item: &str| {
+            let text = self.item_text(item);
+            let item_words: Vec<&str> = words(&text, &item_sep).collect();
+            !item_words
+                .iter()
+                .any(|w| excluded.iter().any(|e| w.starts_with(e.as_str())))
+                && phrases.iter().all(|phrase| {
+                    item_words
+                        .windows(phrase.len())
+                        .any(|window| window.iter().eq(phrase.iter()))
+                })
+        };
+        let hooks = Hooks {
+            filter: Some(&filter),
+            ..Hooks::new(config)
+        };
+        let space = config.separators().first().copied().unwrap_or(' ');
+        let text = query
+            .words
+            .iter()
+            .chain(&query.phrases)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(space.encode_utf8(&mut [0; 4]));
+        self.search(&text, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.item)
+            .collect()
+    }
+
+    pub fn matches_faceted<K: Hash + Ord>(
+        &self,
+        query: &str,
+        facet: impl Fn(&str) -> Option<K>,
+    ) -> (Vec<&'a str>, Vec<(K, usize)>) {
+        self.matches_faceted_with(query, &self.config, facet)
+    }
+
+    /// Like `matches_with`, also counting the items of each facet value
+    /// (`facet` maps an item to e.g. its brand) over every candidate that
+    /// qualified, not just the returned page. Counts come most common
+    /// first, ties by value.
+    pub fn matches_faceted_with<K: Hash + Ord>(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        facet: impl Fn(&str) -> Option<K>,
+    ) -> (Vec<&'a str>, Vec<(K, usize)>) {
+        let counts: RefCell<FxHashMap<K, usize>> = RefCell::default();
+        let visit = |item: &str| {
+            if let Some(value) = facet(item) {
+                *counts.borrow_mut().entry(value).or_default() += 1;
+            }
+        };
+        let hooks = Hooks {
+            visit: Some(&visit),
+            ..Hooks::new(config)
+        };
+        let items = self
+            .search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.item)
+            .collect();
+        let mut counts: Vec<(K, usize)> = counts.into_inner().into_iter().collect();
+        counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        (items, counts)
+    }
+
+    pub fn matches_grouped<K: Hash + Eq + Clone>(
+        &self,
+        query: &str,
+        group: impl Fn(&str) -> K,
+        per_group: usize,
+    ) -> Vec<(K, Vec<&'a str>)> {
+        self.matches_grouped_with(query, &self.config, group, per_group)
+    }
+
+    /// Like `matches_with`, taking at most `per_group` results of each
+    /// group (`group` maps an item to e.g. its brand or file extension), so
+    /// one group can't crowd out the rest. Groups come in the order of
+    /// their best result, each holding its results in rank order.
+    pub fn matches_grouped_with<K: Hash + Eq + Clone>(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        group: impl Fn(&str) -> K,
+        per_group: usize,
+    ) -> Vec<(K, Vec<&'a str>)> {
+        let counts: RefCell<FxHashMap<K, usize>> = RefCell::default();
+        let admit = |item: &str| {
+            let mut counts = counts.borrow_mut();
+            let count = counts.entry(group(item)).or_default();
+            *count += 1;
+            *count <= per_group
+        };
+        let hooks = Hooks {
+            admit: Some(&admit),
+            ..Hooks::new(config)
+        };
+        let mut groups: Vec<(K, Vec<&'a str>)> = vec![];
+        let mut at: FxHashMap<K, usize> = FxHashMap::default();
+        for m in self
+            .search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default()
+        {
+            let key = group(m.item);
+            let i = *at.entry(key.clone()).or_insert_with(|| {
+                groups.push((key, vec![]));
+                groups.len() - 1
+            });
+            groups[i].1.push(m.item);
+        }
+        groups
+    }
+
+    pub fn matches_reranked(
+        &self,
+        query: &str,
+        rerank: impl Fn(&str, f32) -> f32,
+    ) -> Vec<Match<'a>> {
+        self.matches_reranked_with(query, &self.config, rerank)
+    }
+
+    /// Like `matches_scored_with`, but `rerank` maps each candidate's score
+    /// to a new one before the cut to `limit`, so outside signals (clicks,
+    /// popularity) can reorder the whole pool. Results carry and are sorted
+    /// by the new score, ties keeping the default order.
+    pub fn matches_reranked_with(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        rerank: impl Fn(&str, f32) -> f32,
+    ) -> Vec<Match<'a>> {
+        let hooks = Hooks {
+            rerank: Some(&rerank),
+            ..Hooks::new(config)
+        };
+        self.search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default()
+    }
+
+    /// A [`Session`] for a query typed one keystroke at a time.
+    pub fn session(&self) -> Session<'_, 'a, S> {
+        Session::new(self)
+    }
+
+    /// Like `matches_with`, but gives up and returns `None` soon after
+    /// `cancel` is set, checking between trigram probes and while ranking.
+    pub fn matches_cancellable(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        cancel: &CancelToken,
+    ) -> Option<Vec<&'a str>> {
+        let hooks = Hooks {
+            cancel: Some(cancel),
+            ..Hooks::new(config)
+        };
+        let results = self.search(query, config, &hooks, &mut Scratch::default())?;
+        Some(results.into_iter().map(|m| m.item).collect())
+    }
+
+    /// Like `matches_cancellable`, but once `cancel` is set it stops
+    /// probing and ranks the candidates gathered so far, flagged as
+    /// truncated, so a UI can still show something for a stale query.
+    pub fn matches_until_cancelled(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        cancel: &CancelToken,
+    ) -> Matches<'a> {
+        let hooks = Hooks {
+            cancel: Some(cancel),
+            partial: true,
+            ..Hooks::new(config)
+        };
+        let items = self
+            .search(query, config, &hooks, &mut Scratch::default())
+            .unwrap_or_default();
+        Matches {
+            items,
+            truncated: hooks.truncated.get(),
+            total: hooks.total.get(),
+        }
+    }
+
+    /// Async flavor of `matches_cancellable` with the matcher's config. It
+    /// yields to the executor before resolving each query word and again
+    /// before ranking, checking `cancel` each time, so a query superseded
+    /// by the next keystroke stops between steps and a long query shares
+    /// the executor thread.
+    ///
+    /// The last step, from intersecting to ranking, runs inside a single
+    /// poll (checking the token between trigram probes and while ranking).
+    /// Run heavy typo'd queries on large corpora on a blocking thread
+    /// instead, e.g. with `spawn_blocking`.
+    pub async fn matches_async(&self, query: &str, cancel: &CancelToken) -> Option<Vec<&'a str>> {
+        let config = &self.config;
+        if self.check_query(query, config).is_err() {
+            return Some(vec![]);
+        }
+        let mut resolved = Resolved(FxHashMap::default());
+        for word in self.query_words(query, config) {
+            cancel::YieldNow::default().await;
+            if cancel.is_cancelled() {
+                return None;
+            }
+            if let hash_map::Entry::Vacant(entry) = resolved.0.entry(word) {
+                let items = self.resolve(entry.key(), config);
+                entry.insert(items);
+            }
+        }
+        cancel::YieldNow::default().await;
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let hooks = Hooks {
+            cancel: Some(cancel),
+            resolved: Some(&resolved),
+            ..Hooks::new(config)
+        };
+        let results = self.search(query, config, &hooks, &mut Scratch::default())?;
+        Some(results.into_iter().map(|m| m.item).collect())
+    }
+
+    /// The words of `query` that `run_search` resolves, those no longer
+    /// than the longest item word.
+    fn query_words(&self, query: &str, config: &QuickMatchConfig) -> Vec<String> {
+        let query = normalize(query, config, self.cjk(config));
+        let query = self.unquote(query, config);
+        let sep = sep_table(config);
+        split_query(&query, &sep, self.cjk(config), config)
+            .into_iter()
+            .filter(|word| word.len() <= self.max_word_len)
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn matches_batch(&self, queries: &[&str]) -> Vec<Vec<&'a str>>
+    where
+        S: Sync,
+    {
+        self.matches_batch_with(queries, &self.config)
+    }
+
+    /// Like `matches_with` for each query in turn, reusing one set of
+    /// scratch buffers for all of them. Spread across the config's
+    /// `parallelism` threads once the matcher holds `parallel_threshold`
+    /// items.
+    pub fn matches_batch_with(
+        &self,
+        queries: &[&str],
+        config: &QuickMatchConfig,
+    ) -> Vec<Vec<&'a str>>
+    where
+        S: Sync,
+    {
+        if config.parallelism() != 1 && self.items.len() >= config.parallel_threshold() {
+            return self.matches_batch_parallel(queries, config, config.parallelism());
+        }
+        self.batch(queries, config)
+    }
+
+    fn batch(&self, queries: &[&str], config: &QuickMatchConfig) -> Vec<Vec<&'a str>> {
+        let mut scratch = Scratch::default();
+        queries
+            .iter()
+            .map(|query| {
+                self.search(query, config, &Hooks::new(config), &mut scratch)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|m| m.item)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Like `matches_batch_with`, split into contiguous chunks across up to
+    /// `threads` scoped threads (0 for one per available core). Results keep
+    /// the order of `queries`.
+    pub fn matches_batch_parallel(
+        &self,
+        queries: &[&str],
+        config: &QuickMatchConfig,
+        threads: usize,
+    ) -> Vec<Vec<&'a str>>
+    where
+        S: Sync,
+    {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let chunk = queries.len().div_ceil(threads).max(1);
+        if chunk >= queries.len() {
+            return self.batch(queries, config);
+        }
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = queries
+                .chunks(chunk)
+                .map(|queries| scope.spawn(move || self.batch(queries, config)))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// Runs a query, or returns `None` when cancelled through `hooks`.
+    fn search(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        hooks: &Hooks<S>,
+        scratch: &mut Scratch,
+    ) -> Option<Vec<Match<'a>>> {
+        let mut results = self.run_search(query, config, hooks, scratch);
+        if config.input_order()
+            && let Some(results) = &mut results
+        {
+            results.sort_by_key(|m| self.position(m.item));
+        }
+        hooks.check_slow(query, config);
+        results
+    }
+
+    fn run_search(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+        hooks: &Hooks<S>,
+        scratch: &mut Scratch,
+    ) -> Option<Vec<Match<'a>>> {
+        if let Some(metrics) = config.metrics() {
+            metrics.query();
+        }
+        let trigram_budget = self.trigram_budget(config);
+
+        let original = query;
+        let query = normalize(query, config, self.cjk(config));
+        let max_query_len = config.max_query_len().unwrap_or(self.max_query_len);
+
+        if query.is_empty() || query.len() > max_query_len {
+            return Some(vec![]);
+        }
+
+        // The cache holds final pages, so a rerank needs the full pool
+        if ptr::eq(config, &self.config)
+            && hooks.cacheable()
+            && let Some((results, total)) = self.warm.get(&query)
+        {
+            if let Some(metrics) = config.metrics() {
+                metrics.cache_hit();
+            }
+            hooks.total.set(*total);
+            return Some(results.clone());
+        }
+
+        // The query splits under `config`, items as they were indexed
+        let sep = sep_table(config);
+        let item_sep = sep_table(&self.config);
+        let Some(phrases) = self.phrases(&query, &sep) else {
+            return Some(vec![]);
+        };
+        let query = self.unquote(query, config);
 
-impl<'a> QuickMatch<'a> {
-    /// Expect the items to be pre-formatted (lowercase)
-    pub fn new(items: &[&'a str]) -> Self {
-        Self::new_with(items, QuickMatchConfig::default())
-    }
+        let mut query_words = split_query(&query, &sep, self.cjk(config), config);
+        query_words.retain(|w| w.len() <= self.max_word_len);
+        hooks.end_phase(Phase::Normalize);
 
-    /// Expect the items to be pre-formatted (lowercase)
-    pub fn new_with(items: &[&'a str], config: QuickMatchConfig) -> Self {
-        let mut word_index: FxHashMap<String, FxHashSet<*const str>> = FxHashMap::default();
-        let mut trigram_index: FxHashMap<[char; 3], FxHashSet<*const str>> = FxHashMap::default();
-        let mut max_word_len = 0;
-        let mut max_query_len = 0;
-        let mut max_words = 0;
-        let sep = sep_table(config.separators());
-
-        for &item in items {
-            max_query_len = max_query_len.max(item.len());
-            let item_words: Vec<&str> = words(item, &sep).collect();
-            max_words = max_words.max(item_words.len());
-
-            for word in &item_words {
-                max_word_len = max_word_len.max(word.len());
-
-                for len in 1..=word.len() {
-                    word_index
-                        .entry(word[..len].to_string())
-                        .or_default()
-                        .insert(item);
+        if query_words.is_empty() || query_words.len() > self.max_word_count {
+            return Some(vec![]);
+        }
+
+        let mut unknown_words: Vec<&str> = vec![];
+        let mut unresolved: Vec<&str> = vec![];
+        let mut resolved: Vec<Cow<Postings<S>>> = vec![];
+        // Sorted id lists of the resolved words, while all are exact words
+        let mut resolved_ids: Option<Vec<&[u32]>> = Some(vec![]);
+
+        for &word in &query_words {
+            let items = match hooks.resolved.and_then(|resolved| resolved.0.get(word)) {
+                Some(items) => items.as_deref().map(Cow::Borrowed),
+                None => self.resolve(word, config),
+            };
+            let items = items.or_else(|| {
+                hooks
+                    .sound_alike
+                    .get()
+                    .then(|| self.sounds_like(word).map(Cow::Borrowed))
+                    .flatten()
+            });
+            if let Some(items) = items {
+                let exact = self
+                    .word_index
+                    .get(word)
+                    .is_some_and(|postings| ptr::eq(items.as_ref(), postings));
+                resolved_ids = resolved_ids.filter(|_| exact).and_then(|mut lists| {
+                    lists.push(self.word_ids.get(word)?);
+                    Some(lists)
+                });
+                resolved.push(items)
+            } else {
+                unresolved.push(word);
+                let probed = word.is_ascii() || self.config.grapheme_grams();
+                if word.len() >= 3 && probed && unknown_words.len() < trigram_budget {
+                    unknown_words.push(word)
+                }
+            }
+        }
+
+        let known_sets: Vec<&Postings<S>> = resolved.iter().map(|s| s.as_ref()).collect();
+
+        let any_word = config.any_word();
+        // Narrowing the pool of the query this one refines beats walking
+        // postings, when every word resolved, it is the smaller, and it
+        // still holds matches
+        let smallest = known_sets.iter().map(|set| set.len()).min();
+        let narrowed = hooks
+            .within
+            .filter(|within| !any_word && unresolved.is_empty() && smallest > Some(within.len()))
+            .map(|within| -> Postings<S> {
+                within
+                    .iter()
+                    .copied()
+                    .filter(|item| known_sets.iter().all(|set| set.contains(item)))
+                    .collect()
+            })
+            .filter(|pool| !pool.is_empty());
+        let pool = if any_word {
+            None
+        } else if narrowed.is_some() {
+            narrowed
+        } else if let Some(mut lists) = resolved_ids.filter(|lists| !lists.is_empty()) {
+            let ids = gallop::intersect(&mut lists);
+            (!ids.is_empty()).then(|| {
+                ids.into_iter()
+                    .map(|id| self.items[id as usize] as *const str)
+                    .collect()
+            })
+        } else {
+            Self::intersect_sets(&known_sets)
+        };
+        hooks.end_phase(Phase::Intersect);
+        // A rewritten query's pool isn't one the original refines
+        if let (Some(keep), Some(pool)) = (hooks.keep_pool, &pool)
+            && !hooks.rewritten.get()
+        {
+            keep.replace(pool.iter().copied().collect());
+        }
+
+        // Each query word whose postings hold the item is worth one point;
+        // the unknown words share their trigram overlap on top.
+        let relevance = |item: *const str, fuzzy: f32| {
+            let known = known_sets.iter().filter(|set| set.contains(&item)).count();
+            (known as f32 + fuzzy * unknown_words.len() as f32).min(query_words.len() as f32)
+                / query_words.len() as f32
+        };
+
+        // Query words an item must satisfy, with `min_coverage`
+        let needed = (config.min_coverage() * query_words.len() as f32).ceil() as usize;
+        let covers = |item: *const str| {
+            needed == 0
+                || known_sets.iter().filter(|set| set.contains(&item)).count()
+                    + unresolved
+                        .iter()
+                        .filter(|word| self.shares_trigrams(item, word))
+                        .count()
+                    >= needed
+        };
+
+        // Fuzzy scores other than trigram hits, scaled to integers
+        let scoring = config.scoring();
+        let query_grams: Vec<FxHashSet<Gram>> = match scoring {
+            Scoring::Jaccard => unknown_words
+                .iter()
+                .map(|word| self.grams_of(word))
+                .collect(),
+            _ => vec![],
+        };
+        let query_terms = match scoring {
+            Scoring::Cosine => self.term_counts(query_words.iter().copied()),
+            _ => FxHashMap::default(),
+        };
+        let similarity = |item: *const str| {
+            let item = unsafe { &*item };
+            let similarity = match scoring {
+                Scoring::Hits => return None,
+                Scoring::Jaccard => self.word_similarity(item, &query_grams),
+                Scoring::Cosine => {
+                    let text = self.item_text(item);
+                    cosine(&query_terms, &self.term_counts(words(&text, &item_sep)))
                 }
+            };
+            Some((similarity * SIMILARITY_SCALE) as usize)
+        };
 
-                let mut chars = word.chars();
-                if let (Some(mut a), Some(mut b)) = (chars.next(), chars.next()) {
-                    for c in chars {
-                        trigram_index.entry([a, b, c]).or_default().insert(item);
-                        a = b;
-                        b = c;
+        // Try typo matching for unknown words, unless the known ones
+        // already fill a page and the config is happy with that
+        let pool_full = config.pool_shortcut()
+            && pool
+                .as_ref()
+                .is_some_and(|pool| pool.len() >= config.limit());
+        if !unknown_words.is_empty() && trigram_budget > 0 && !pool_full {
+            // An item needs not hold the other words when any word will do
+            let min_len = if any_word {
+                unknown_words.iter().map(|w| w.len()).min().unwrap_or(0)
+            } else {
+                query.len()
+            }
+            .saturating_sub(3);
+            let hit_count = self.score_trigrams(
+                &unknown_words,
+                config,
+                pool.as_ref(),
+                min_len,
+                hooks,
+                scratch,
+            )?;
+            let unit = self.trigram_unit(config);
+            let min_hits = (hit_count as f32 * config.min_hit_ratio()).ceil() as usize;
+            let min_score = min_hits.max(config.min_score() * unit);
+            let bonus = unit * usize::from(pool.is_some());
+            if any_word {
+                // Items with a known word stay in, typo matches or not
+                for set in &known_sets {
+                    for &item in *set {
+                        scratch.scores.entry(item).or_insert(0);
                     }
                 }
             }
+            let known = |item: *const str| known_sets.iter().any(|set| set.contains(&item));
+            let results = self.rank(
+                scratch
+                    .scores
+                    .drain()
+                    .filter(|&(item, s)| s >= min_score || (any_word && known(item)))
+                    .filter(|&(item, _)| self.has_phrases(item, &phrases) && covers(item))
+                    .map(|(item, s)| (item, similarity(item).unwrap_or(s))),
+                |item, score| {
+                    let fuzzy = if scoring != Scoring::Hits {
+                        score as f32 / SIMILARITY_SCALE
+                    } else {
+                        (score - bonus) as f32 / hit_count.max(1) as f32
+                    };
+                    relevance(item, fuzzy)
+                },
+                &query_words,
+                &item_sep,
+                config,
+                hooks,
+            )?;
 
-            for pair in item_words.windows(2) {
-                let compound = format!("{}{}", pair[0], pair[1]);
-                // A joined-word query ("hashrate") can be longer than any
-                // single word. Capping at the longest index key keeps the
-                // DDoS guard data-bounded while still letting it match.
-                max_word_len = max_word_len.max(compound.len());
-                let from = pair[0].len() + 1;
-                for len in from..=compound.len() {
-                    word_index
-                        .entry(compound[..len].to_string())
-                        .or_default()
-                        .insert(item);
-                }
+            if !results.is_empty() || hooks.emitted.get() > 0 || hooks.cancelled() {
+                return Some(results);
             }
         }
 
-        Self {
-            max_query_len: max_query_len + 6,
-            max_word_len: max_word_len + 4,
-            max_word_count: max_words + 2,
-            word_index,
-            trigram_index,
+        // Typo matching found nothing: retry with the nearest item words
+        let space = config.separators().first().copied().unwrap_or(' ');
+        if config.match_mode().typos()
+            && !hooks.rewritten.replace(true)
+            && let Some(rewritten) = self.nearest_rewrite(&query_words, &unresolved, space)
+        {
+            return self.run_search(&rewritten, config, hooks, scratch);
+        }
+
+        // Nor a close spelling: retry with the words that sound alike
+        if config.match_mode() == MatchMode::Hybrid
+            && !pool_full
+            && unresolved
+                .iter()
+                .any(|word| self.sounds_like(word).is_some())
+            && !hooks.sound_alike.replace(true)
+        {
+            hooks.rewritten.set(true);
+            return self.run_search(original, config, hooks, scratch);
+        }
+
+        // Rank known candidates (intersection, or union as fallback)
+        let candidates = pool.unwrap_or_else(|| Self::union_sets(&known_sets));
+        self.rank(
+            candidates
+                .into_iter()
+                .filter(|&item| self.has_phrases(item, &phrases) && covers(item))
+                .map(|p| (p, similarity(p).unwrap_or(0))),
+            |item, _| relevance(item, 0.0),
+            &query_words,
+            &item_sep,
             config,
-            _phantom: PhantomData,
+            hooks,
+        )
+    }
+
+    pub fn matches_subsequence(&self, query: &str) -> Vec<&'a str> {
+        self.matches_subsequence_with(query, &self.config)
+    }
+
+    /// Alternative to word matching for file and symbol pickers: every item
+    /// holding the query's chars in order ("qmcfg" → "quick_match_config"),
+    /// ranked fzf-style by how tightly and on which word starts they land.
+    /// Separators in the query are ignored.
+    pub fn matches_subsequence_with(&self, query: &str, config: &QuickMatchConfig) -> Vec<&'a str> {
+        let query = normalize(query, config, self.cjk(config));
+        let max_query_len = config.max_query_len().unwrap_or(self.max_query_len);
+        if query.len() > max_query_len {
+            return vec![];
         }
+
+        let sep = sep_table(config);
+        let pattern: Vec<u8> = query.bytes().filter(|&b| !sep[b as usize]).collect();
+        if pattern.is_empty() {
+            return vec![];
+        }
+
+        let item_sep = sep_table(&self.config);
+        let mut scored: Vec<(&'a str, i32)> = self
+            .items
+            .iter()
+            .filter_map(|&item| {
+                subsequence::subsequence_score(item, &pattern, &item_sep).map(|score| (item, score))
+            })
+            .collect();
+        scored.sort_unstable_by(|a, b| {
+            b.1.cmp(&a.1) // subsequence score, desc
+                .then(a.0.len().cmp(&b.0.len())) // item length, asc
+                .then(a.0.cmp(b.0)) // item text, asc (total order)
+        });
+        scored.dedup_by_key(|(item, _)| *item as *const str);
+        scored.truncate(config.limit());
+        scored.into_iter().map(|(item, _)| item).collect()
     }
 
-    pub fn matches(&self, query: &str) -> Vec<&'a str> {
-        self.matches_with(query, &self.config)
+    pub fn matches_report(&self, query: &str) -> Vec<MatchReport<'a>> {
+        self.matches_report_with(query, &self.config)
     }
 
-    pub fn matches_with(&self, query: &str, config: &QuickMatchConfig) -> Vec<&'a str> {
-        let limit = config.limit();
-        let trigram_budget = config.trigram_budget();
+    /// Like `matches_scored_with`, also splitting the normalized query words
+    /// into the ones each result satisfies (exactly or fuzzily) and the rest,
+    /// with the edit distance behind each fuzzy one.
+    pub fn matches_report_with(
+        &self,
+        query: &str,
+        config: &QuickMatchConfig,
+    ) -> Vec<MatchReport<'a>> {
+        let results = self.matches_scored_with(query, config);
+        let normalized = self.unquote(normalize(query, config, self.cjk(config)), config);
+        let sep = sep_table(config);
+        let query_words = split_query(&normalized, &sep, self.cjk(config), config);
+
+        results
+            .into_iter()
+            .map(|Match { item, score }| {
+                let mut report = MatchReport {
+                    item,
+                    score,
+                    matched: vec![],
+                    unmatched: vec![],
+                    distances: vec![],
+                };
+                for &word in &query_words {
+                    if self.satisfies(item, word, config) {
+                        report.matched.push(word.to_string());
+                        if self.resolve(word, config).is_none()
+                            && let Some(distance) = self
+                                .words_of(item)
+                                .iter()
+                                .map(|w| distance::edit_distance(word, w))
+                                .min()
+                        {
+                            report.distances.push((word.to_string(), distance));
+                        }
+                    } else {
+                        report.unmatched.push(word.to_string());
+                    }
+                }
+                report
+            })
+            .collect()
+    }
+
+    /// Items matching a query word without trigram fuzzing, as far as the
+    /// config's `match_mode` allows: through the word index (or the words
+    /// it prefixes, with `prefix_expansion`), along with the words holding
+    /// it further in, with `infix_expansion`, then for missing words
+    /// through acronyms, adjacent-key variants when the
+    /// config names a keyboard layout, or item words within the config's
+    /// `max_edits`. With `number_words`, a small number also finds its
+    /// other spelling.
+    fn resolve(&self, word: &str, config: &QuickMatchConfig) -> Option<Cow<'_, Postings<S>>> {
+        let items = self.resolve_word(word, config);
+        let Some(other) = numbers::equivalent(word).filter(|_| config.number_words()) else {
+            return items;
+        };
+        match (items, self.resolve_word(&other, config)) {
+            (Some(items), Some(more)) => {
+                let mut items = items.into_owned();
+                items.extend(more.iter());
+                Some(Cow::Owned(items))
+            }
+            (items, more) => items.or(more),
+        }
+    }
+
+    fn resolve_word(&self, word: &str, config: &QuickMatchConfig) -> Option<Cow<'_, Postings<S>>> {
+        match config.match_mode() {
+            MatchMode::ExactWords => {
+                return self
+                    .word_counts
+                    .contains_key(word)
+                    .then(|| self.word_index.get(word).map(Cow::Borrowed))
+                    .flatten();
+            }
+            MatchMode::Prefix => return self.resolve_prefix(word, config),
+            MatchMode::Fuzzy => return None,
+            MatchMode::Hybrid => {}
+        }
+        if let Some(items) = self.resolve_prefix(word, config) {
+            return Some(items);
+        }
+        if let Some(items) = self.acronym_index.get(word) {
+            return Some(Cow::Borrowed(items));
+        }
+        if let Some(items) = self.adjacent_key_variants(word, config) {
+            return Some(items);
+        }
+        if let Some(items) = self.single_edit_variants(word, config) {
+            return Some(items);
+        }
+        self.within_edits(word, config)
+    }
 
-        let query: String = query
-            .trim()
-            .chars()
-            .filter(|c| c.is_ascii())
-            .map(|c| c.to_ascii_lowercase())
+    /// The query with each word nothing resolves replaced by its nearest
+    /// item word, up to one edit per 3 chars (at least 1, at most 2), when
+    /// built with `bk_tree` and one was found.
+    fn nearest_rewrite(
+        &self,
+        query_words: &[&str],
+        unresolved: &[&str],
+        sep: char,
+    ) -> Option<String> {
+        if self.bk_tree.is_empty() {
+            return None;
+        }
+        let mut replaced = false;
+        let words: Vec<&str> = query_words
+            .iter()
+            .map(|&word| {
+                if !unresolved.contains(&word) {
+                    return word;
+                }
+                let max = (word.chars().count() / 3).clamp(1, 2);
+                match self
+                    .bk_tree
+                    .find(word, max)
+                    .into_iter()
+                    .min_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)))
+                {
+                    Some((nearest, _)) => {
+                        replaced = true;
+                        nearest
+                    }
+                    None => word,
+                }
+            })
             .collect();
+        replaced.then(|| words.join(sep.encode_utf8(&mut [0; 4])))
+    }
 
-        if query.is_empty() || query.len() > self.max_query_len {
-            return vec![];
+    /// Union of the postings of the variants of `word` with one char
+    /// swapped for an adjacent key, when the config names a layout.
+    fn adjacent_key_variants(
+        &self,
+        word: &str,
+        config: &QuickMatchConfig,
+    ) -> Option<Cow<'_, Postings<S>>> {
+        let layout = config
+            .keyboard_layout()
+            .filter(|_| word.len() >= 3 && word.is_ascii())?;
+        let mut variant = word.to_string();
+        let mut items: Postings<S> = HashSet::default();
+        for (i, c) in word.char_indices() {
+            for key in layout.neighbors(c) {
+                variant.replace_range(i..i + 1, key.encode_utf8(&mut [0; 4]));
+                if let Some(hits) = self.word_index.get(&variant) {
+                    items.extend(hits);
+                }
+            }
+            variant.replace_range(i..i + 1, c.encode_utf8(&mut [0; 4]));
         }
+        (!items.is_empty()).then_some(Cow::Owned(items))
+    }
 
-        let sep = sep_table(config.separators());
+    /// Items under `word` or the words it starts, with those holding it
+    /// further in under `infix_expansion`.
+    fn resolve_prefix(
+        &self,
+        word: &str,
+        config: &QuickMatchConfig,
+    ) -> Option<Cow<'_, Postings<S>>> {
+        let direct = self
+            .expand_prefix(word, config)
+            .or_else(|| self.word_index.get(word).map(Cow::Borrowed));
+        match (direct, self.expand_infix(word, config)) {
+            (Some(Cow::Borrowed(direct)), Some(infix)) => {
+                let mut items = direct.clone();
+                items.extend(infix.iter());
+                Some(Cow::Owned(items))
+            }
+            (Some(Cow::Owned(mut items)), Some(infix)) => {
+                items.extend(infix.iter());
+                Some(Cow::Owned(items))
+            }
+            (direct, infix) => direct.or(infix),
+        }
+    }
 
-        let mut query_words: Vec<&str> = vec![];
-        for w in words(&query, &sep) {
-            if w.len() <= self.max_word_len && !query_words.contains(&w) {
-                query_words.push(w);
+    /// Items under the first of the config's `edit_expansions` one-edit
+    /// variants of `word` that the word index holds.
+    fn single_edit_variants(
+        &self,
+        word: &str,
+        config: &QuickMatchConfig,
+    ) -> Option<Cow<'_, Postings<S>>> {
+        let budget = config.edit_expansions();
+        if budget == 0 || word.len() < 3 || !word.is_ascii() {
+            return None;
+        }
+        let mut items: Postings<S> = HashSet::default();
+        for variant in distance::single_edits(word).take(budget) {
+            if let Some(hits) = self.word_index.get(&variant) {
+                items.extend(hits);
             }
         }
+        (!items.is_empty()).then_some(Cow::Owned(items))
+    }
 
-        if query_words.is_empty() || query_words.len() > self.max_word_count {
-            return vec![];
+    /// Union of the postings of the item words within the config's
+    /// `max_edits` of `word`, allowing one edit per 3 chars of it.
+    fn within_edits(&self, word: &str, config: &QuickMatchConfig) -> Option<Cow<'_, Postings<S>>> {
+        let max = config.max_edits().min(word.chars().count() / 3);
+        if max == 0 {
+            return None;
+        }
+        let automaton = distance::LevenshteinAutomaton::new(word, max);
+        let mut items: Postings<S> = HashSet::default();
+        for w in automaton.matches(self.word_counts.keys().map(String::as_str)) {
+            if let Some(hits) = self.word_index.get(w) {
+                items.extend(hits);
+            }
         }
+        (!items.is_empty()).then_some(Cow::Owned(items))
+    }
 
-        let mut unknown_words: Vec<&str> = vec![];
-        let mut known_sets: Vec<&FxHashSet<*const str>> = vec![];
+    /// Union of the postings of the first `prefix_expansion` indexed words
+    /// starting with `word`, when built with it.
+    fn expand_prefix(&self, word: &str, config: &QuickMatchConfig) -> Option<Cow<'_, Postings<S>>> {
+        if self.sorted_words.is_empty() {
+            return None;
+        }
+        let max_words = config.prefix_expansion().unwrap_or(usize::MAX);
+        let mut expanded = self
+            .sorted_words
+            .range::<str, _>((Bound::Included(word), Bound::Unbounded))
+            .take_while(|w| w.starts_with(word))
+            .take(max_words)
+            .map(|w| &self.word_index[w]);
 
-        for &word in &query_words {
-            if let Some(items) = self.word_index.get(word) {
-                known_sets.push(items)
-            } else if word.len() >= 3 && unknown_words.len() < trigram_budget {
-                unknown_words.push(word)
+        let first = expanded.next()?;
+        let Some(second) = expanded.next() else {
+            return Some(Cow::Borrowed(first));
+        };
+        let mut items = first.clone();
+        items.extend(second);
+        for set in expanded {
+            items.extend(set);
+        }
+        Some(Cow::Owned(items))
+    }
+
+    /// Items under the item words holding `word` past their start ("book"
+    /// in "macbook"), at most the config's `infix_expansion` of them in
+    /// sorted order.
+    fn expand_infix(&self, word: &str, config: &QuickMatchConfig) -> Option<Cow<'_, Postings<S>>> {
+        let max_words = config.infix_expansion().filter(|_| word.len() >= 3)?;
+        let mut items: Postings<S> = HashSet::default();
+        for w in self
+            .word_counts
+            .keys()
+            .filter(|w| w.match_indices(word).any(|(at, _)| at > 0))
+            .take(max_words)
+        {
+            if let Some(hits) = self.word_index.get(w) {
+                items.extend(hits);
             }
         }
+        (!items.is_empty()).then_some(Cow::Owned(items))
+    }
 
-        let pool = Self::intersect_sets(&known_sets);
+    /// Items with a word that sounds like `word`, when built with
+    /// `phonetic`, for words that nothing else resolves.
+    #[cfg(feature = "phonetic")]
+    fn sounds_like(&self, word: &str) -> Option<&Postings<S>> {
+        if !self.config.phonetic() || word.len() < 3 {
+            return None;
+        }
+        phonetic::phonetic_key(word).and_then(|key| self.phonetic_index.get(&key))
+    }
 
-        // Try typo matching for unknown words
-        if !unknown_words.is_empty() && trigram_budget > 0 {
-            let min_len = query.len().saturating_sub(3);
-            let (scores, hit_count) =
-                self.score_trigrams(&unknown_words, trigram_budget, pool.as_ref(), min_len);
-            let min_score = hit_count.div_ceil(2).max(config.min_score());
-            let results = Self::rank(
-                scores.into_iter().filter(|(_, s)| *s >= min_score),
-                &query_words,
-                &sep,
-                limit,
-            );
+    #[cfg(not(feature = "phonetic"))]
+    fn sounds_like(&self, _word: &str) -> Option<&Postings<S>> {
+        None
+    }
+
+    /// Whether `item` holds `word` as a word, prefix, or compound, or one
+    /// `resolve` maps it to, or else `shares_trigrams` with it or has a
+    /// word that sounds like it.
+    fn satisfies(&self, item: &str, word: &str, config: &QuickMatchConfig) -> bool {
+        let item = item as *const str;
+        if let Some(items) = self.resolve(word, config) {
+            return items.contains(&item);
+        }
+        self.shares_trigrams(item, word)
+            || self
+                .sounds_like(word)
+                .is_some_and(|items| items.contains(&item))
+    }
+
+    /// Whether `item` holds at least half of the trigrams of `word`.
+    fn shares_trigrams(&self, item: *const str, word: &str) -> bool {
+        let units = self.query_units(word);
+        let trigrams: Vec<&[char]> = units.windows(3).collect();
+        let hits = trigrams
+            .iter()
+            .filter(|t| {
+                self.grams()
+                    .index
+                    .get(&gram(t[0], t[1], t[2]))
+                    .is_some_and(|items| items.contains(&item))
+            })
+            .count();
+        !trigrams.is_empty() && hits * 2 >= trigrams.len()
+    }
 
-            if !results.is_empty() {
-                return results;
+    /// The quoted phrases of a normalized query as word ids, when built with
+    /// `phrases`; single-word phrases need no adjacency and are left out.
+    /// Returns `None` when a phrase holds a word no item has, so nothing
+    /// can match.
+    fn phrases(&self, query: &str, sep: &[bool; 256]) -> Option<Vec<Vec<u32>>> {
+        if self.word_sequences.is_empty() {
+            return Some(vec![]);
+        }
+        let mut phrases = vec![];
+        for quoted in query.split('"').skip(1).step_by(2) {
+            let phrase = words(quoted, sep)
+                .map(|w| self.vocabulary.get(w).copied())
+                .collect::<Option<Vec<u32>>>()?;
+            if phrase.len() >= 2 {
+                phrases.push(phrase);
             }
         }
+        Some(phrases)
+    }
 
-        // Rank known candidates (intersection, or union as fallback)
-        let candidates = pool.unwrap_or_else(|| Self::union_sets(&known_sets));
-        Self::rank(
-            candidates.into_iter().map(|p| (p, 0)),
-            &query_words,
-            &sep,
-            limit,
-        )
+    /// Turns the quotes of a query into word breaks, when built with
+    /// `phrases`.
+    fn unquote(&self, query: String, config: &QuickMatchConfig) -> String {
+        if self.word_sequences.is_empty() || !query.contains('"') {
+            return query;
+        }
+        let space = config.separators().first().copied().unwrap_or(' ');
+        query.replace('"', space.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Whether `item` has the words of every phrase next to each other.
+    fn has_phrases(&self, item: *const str, phrases: &[Vec<u32>]) -> bool {
+        if phrases.is_empty() {
+            return true;
+        }
+        self.word_sequences.get(&item).is_some_and(|sequence| {
+            phrases.iter().all(|phrase| {
+                sequence
+                    .windows(phrase.len())
+                    .any(|window| window == &phrase[..])
+            })
+        })
     }
 
     /// Intersection of all sets, or `None` when there are no sets or no
     /// overlap. Clones the smallest set, then narrows it against the rest;
     /// the clone's own source set is skipped since it would change nothing.
-    fn intersect_sets(sets: &[&FxHashSet<*const str>]) -> Option<FxHashSet<*const str>> {
+    fn intersect_sets(sets: &[&Postings<S>]) -> Option<Postings<S>> {
         let (smallest_idx, smallest) = sets
             .iter()
             .copied()
@@ -189,126 +2423,667 @@ impl<'a> QuickMatch<'a> {
     }
 
     /// Union of all sets.
-    fn union_sets(sets: &[&FxHashSet<*const str>]) -> FxHashSet<*const str> {
+    fn union_sets(sets: &[&Postings<S>]) -> Postings<S> {
         sets.iter().flat_map(|s| s.iter().copied()).collect()
     }
 
-    /// Bucket by matched-word count, then sort each needed bucket by fuzzy
-    /// score, match position, and length.
+    /// Bucket by matched-word count (covered-word count with `any_word`),
+    /// then sort each needed bucket by fuzzy score, match position, and
+    /// length, or by the config's `order`. Items whose `relevance` is below
+    /// the config's `min_relevance` are dropped before the cut to `limit`.
+    /// With a `rerank` hook or item weights, every bucket is sorted and the
+    /// whole pool is then reordered by the weighted, reranked score. Stops
+    /// taking candidates once out of time, and returns `None` when
+    /// cancelled.
     fn rank(
+        &self,
         candidates: impl IntoIterator<Item = (*const str, usize)>,
+        relevance: impl Fn(*const str, usize) -> f32,
         query_words: &[&str],
         sep: &[bool; 256],
-        limit: usize,
-    ) -> Vec<&'a str> {
-        let mut buckets: Vec<Vec<(&str, usize, usize)>> = vec![vec![]; query_words.len() + 1];
+        config: &QuickMatchConfig,
+        hooks: &Hooks<S>,
+    ) -> Option<Vec<Match<'a>>> {
+        let limit = config.limit();
+        let min_relevance = config.min_relevance();
+        // With `paths`, each match count splits in two: file name matched
+        // above, directories only below
+        let paths = config.paths();
+        let mut buckets: Vec<Vec<(&str, usize, usize, f32)>> =
+            vec![vec![]; (query_words.len() + 1) * (1 + usize::from(paths))];
+        let mut considered = 0;
+        let mut qualified = 0;
+        let tiebreak = |a: &str, b: &str| match config.tiebreak() {
+            Tiebreak::Text => a.cmp(b),
+            Tiebreak::Insertion => self.position(a).cmp(&self.position(b)).then(a.cmp(b)),
+        };
+        let order = |a: &(&str, usize, usize, f32), b: &(&str, usize, usize, f32)| {
+            if let Some(order) = config.order() {
+                let (x, y) = (
+                    Match {
+                        item: a.0,
+                        score: a.3,
+                    },
+                    Match {
+                        item: b.0,
+                        score: b.3,
+                    },
+                );
+                return order(&x, &y).then_with(|| tiebreak(a.0, b.0));
+            }
+            b.1.cmp(&a.1) // fuzzy score, desc
+                .then(a.2.cmp(&b.2)) // match position, asc
+                .then(a.0.len().cmp(&b.0.len())) // item length, asc
+                .then_with(|| tiebreak(a.0, b.0)) // text or insertion, asc (total order)
+        };
+
+        // Nothing past the cut can drop or reorder results, so only the
+        // best `limit` need keeping, in a heap instead of full buckets
+        let bounded = hooks.rerank.is_none()
+            && self.weights.is_empty()
+            && config.collapse().is_none()
+            && hooks.admit.is_none();
+        let mut top = bounded.then(|| {
+            TopK::new(
+                limit,
+                |a: &(usize, (&str, usize, usize, f32)), b: &(usize, _)| {
+                    b.0.cmp(&a.0).then_with(|| order(&a.1, &b.1))
+                },
+            )
+        });
 
-        for (item, fuzzy) in candidates {
+        for (i, (item, fuzzy)) in candidates.into_iter().enumerate() {
+            if i % 1024 == 0 {
+                match hooks.stop() {
+                    Some(true) => break,
+                    Some(false) => return None,
+                    None => {}
+                }
+            }
+            // Past the deadline, rank what's gathered (at least a full page)
+            if i >= limit && i % 256 == 0 && hooks.out_of_time() {
+                break;
+            }
+            if config.max_candidates().is_some_and(|max| i >= max) {
+                hooks.truncated.set(true);
+                break;
+            }
+            considered = i + 1;
+            if hooks
+                .language
+                .is_some_and(|language| self.languages.get(&item) != Some(&language))
+            {
+                continue;
+            }
             let s = unsafe { &*item as &'a str };
-            let (matched, position) = word_match(s, query_words, sep);
-            buckets[matched].push((s, fuzzy, position));
+            if hooks.filter.is_some_and(|filter| !filter(s)) {
+                continue;
+            }
+            let score = relevance(item, fuzzy);
+            if score < min_relevance {
+                continue;
+            }
+            if let Some(visit) = hooks.visit {
+                visit(s);
+            }
+            let text = self.item_text(s);
+            let (mut matched, position) = word_match(&text, query_words, sep);
+            if config.any_word() {
+                matched = (score * query_words.len() as f32).round() as usize;
+            }
+            if paths {
+                let file_name = text.rsplit(['/', '\\']).next().unwrap_or_default();
+                let in_file_name = words(file_name, sep)
+                    .any(|word| query_words.iter().any(|qw| word.starts_with(qw)));
+                matched = 2 * matched + usize::from(in_file_name);
+            }
+            qualified += 1;
+            match &mut top {
+                Some(top) => top.push((matched, (s, fuzzy, position, score))),
+                None => buckets[matched].push((s, fuzzy, position, score)),
+            }
+        }
+        if let Some(metrics) = config.metrics() {
+            metrics.candidates(considered);
+        }
+        hooks.total.set(qualified);
+
+        if let Some(top) = top {
+            let mut results = vec![];
+            hooks.deliver(
+                top.into_sorted_vec()
+                    .into_iter()
+                    .map(|(_, (item, .., score))| Match { item, score }),
+                &mut results,
+            );
+            hooks.end_phase(Phase::Rank);
+            return Some(results);
+        }
+
+        // With `collapse`, the fingerprints of the results taken so far
+        let mut taken: Vec<(Vec<String>, FxHashSet<Gram>)> = vec![];
+        let mut admit = |item: &str| {
+            let Some(threshold) = config.collapse() else {
+                return hooks.admits(item);
+            };
+            let (words, grams) = self.fingerprint(item);
+            let duplicate = taken
+                .iter()
+                .any(|(w, g)| *w == words || jaccard(g, &grams) >= threshold);
+            if duplicate || !hooks.admits(item) {
+                return false;
+            }
+            taken.push((words, grams));
+            true
+        };
+
+        if hooks.rerank.is_some() || !self.weights.is_empty() {
+            let blend = config.weight_blend();
+            let mut results = vec![];
+            for bucket in buckets.iter_mut().rev() {
+                bucket.sort_unstable_by(order);
+                results.extend(bucket.iter().map(|&(item, .., score)| {
+                    let weighted = score + blend * self.weight(item);
+                    let key = hooks
+                        .rerank
+                        .map_or(weighted, |rerank| rerank(item, weighted));
+                    // Weights reorder; only a rerank hook rewrites the score
+                    let score = if hooks.rerank.is_some() { key } else { score };
+                    (Match { item, score }, key)
+                }));
+            }
+            // Stable, so equal reranked scores keep the default order
+            results.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let mut page = vec![];
+            hooks.deliver(
+                results
+                    .into_iter()
+                    .map(|(m, _)| m)
+                    .filter(|m| admit(m.item))
+                    .take(limit),
+                &mut page,
+            );
+            hooks.end_phase(Phase::Rank);
+            return Some(page);
         }
 
-        let mut results = Vec::with_capacity(limit);
+        let mut results = Vec::with_capacity(if hooks.emit.is_some() { 0 } else { limit });
+        let mut passed = 0;
         for bucket in buckets.iter_mut().rev() {
             if bucket.is_empty() {
                 continue;
             }
-            bucket.sort_unstable_by(|a, b| {
-                b.1.cmp(&a.1) // fuzzy score, desc
-                    .then(a.2.cmp(&b.2)) // match position, asc
-                    .then(a.0.len().cmp(&b.0.len())) // item length, asc
-                    .then(a.0.cmp(b.0)) // item text, asc (total order)
-            });
-            results.extend(bucket.iter().take(limit - results.len()).map(|&(s, ..)| s));
-            if results.len() >= limit {
+            bucket.sort_unstable_by(order);
+            let Some(n) = hooks.deliver(
+                bucket
+                    .iter()
+                    .filter(|&&(item, ..)| admit(item))
+                    .take(limit - passed)
+                    .map(|&(item, .., score)| Match { item, score }),
+                &mut results,
+            ) else {
+                break;
+            };
+            passed += n;
+            if passed >= limit {
                 break;
             }
         }
 
-        results
+        hooks.end_phase(Phase::Rank);
+        Some(results)
+    }
+
+    /// The config's trigram budget, or the corpus-picked one if it asks.
+    fn trigram_budget(&self, config: &QuickMatchConfig) -> usize {
+        if self.config.words_only() || !config.match_mode().typos() {
+            0
+        } else if config.auto_trigram_budget() {
+            self.auto_trigram_budget()
+        } else {
+            config.trigram_budget()
+        }
+    }
+
+    /// Score one trigram hit is worth: 2 when weighing positions, so a hit
+    /// far from its spot in the indexed word can earn half (1), else 1.
+    fn trigram_unit(&self, config: &QuickMatchConfig) -> usize {
+        if config.positional_trigrams() && !self.grams().positions.is_empty() {
+            2
+        } else {
+            1
+        }
     }
 
     /// Builds per-item trigram-overlap scores for the unknown (typo) words.
-    /// With a `pool`, only pooled items can score (each pre-seeded to 1);
-    /// otherwise any item at least `min_len` chars long is eligible. Returns
-    /// the score map and how many probed trigrams were found in the index.
+    /// With a `pool`, only pooled items can score (each pre-seeded to one
+    /// unit); otherwise any item at least `min_len` chars long is eligible.
+    /// Fills `scratch.scores` and returns the units earned by probed
+    /// trigrams found in the index. The budget is shared by all words, or
+    /// granted to each word in full with `per_word_budget`. With
+    /// `skip_grams`, each probe also looks the trigram up among the
+    /// skip-grams, and the skip-gram starting at the same position among
+    /// the trigrams, so adjacent swaps still hit. Stops probing once out of
+    /// time, and returns `None` when cancelled.
     fn score_trigrams(
         &self,
         unknown_words: &[&str],
-        trigram_budget: usize,
-        pool: Option<&FxHashSet<*const str>>,
+        config: &QuickMatchConfig,
+        pool: Option<&Postings<S>>,
         min_len: usize,
-    ) -> (FxHashMap<*const str, usize>, usize) {
-        let mut scores: FxHashMap<*const str, usize> = FxHashMap::default();
+        hooks: &Hooks<S>,
+        scratch: &mut Scratch,
+    ) -> Option<usize> {
+        let trigram_budget = self.trigram_budget(config);
+        let unit = self.trigram_unit(config);
+        let per_word = config.per_word_budget();
+        let max_candidates = config.max_candidates().unwrap_or(usize::MAX);
+        let Scratch {
+            scores,
+            visited,
+            visited_skip_grams,
+        } = scratch;
+        scores.clear();
+        visited.clear();
+        visited_skip_grams.clear();
         scores.reserve(256);
         if let Some(pool) = pool {
             for &item in pool {
-                scores.insert(item, 1);
+                scores.insert(item, unit);
             }
         }
         let has_pool = pool.is_some();
 
-        let mut budget = trigram_budget;
+        let mut budgets = vec![trigram_budget; if per_word { unknown_words.len() } else { 1 }];
         let mut hit_count = 0;
-        let mut visited: FxHashSet<[char; 3]> = FxHashSet::default();
+        let grams = self.grams();
+        let use_skip_grams = config.skip_grams() && !grams.skip_grams.is_empty();
+
+        // Each word in the units its grams are windowed over when indexed
+        let units: Vec<Vec<char>> = unknown_words
+            .iter()
+            .map(|word| self.query_units(word))
+            .collect();
+
+        // With `rarest_first`, each word's trigram positions by ascending
+        // postings length, leaving out trigrams no item has
+        let rarest: Vec<Vec<usize>> = if config.rarest_first() {
+            units
+                .iter()
+                .map(|units| {
+                    let mut positions: Vec<(usize, usize)> = (0..units.len().saturating_sub(2))
+                        .filter_map(|pos| {
+                            let trigram = gram(units[pos], units[pos + 1], units[pos + 2]);
+                            grams.index.get(&trigram).map(|items| (items.len(), pos))
+                        })
+                        .collect();
+                    positions.sort_unstable();
+                    positions.into_iter().map(|(_, pos)| pos).collect()
+                })
+                .collect()
+        } else {
+            vec![]
+        };
 
-        'outer: for round in 0..trigram_budget {
-            for word in unknown_words {
-                if budget == 0 {
-                    break 'outer;
+        // A word may skip rounds (no fresh position, or an already visited
+        // trigram), so spending a full per-word budget can take more rounds
+        // than the budget itself; every position is offered within 2 * len.
+        let rounds = if per_word {
+            let longest = units.iter().map(Vec::len).max().unwrap_or(0);
+            trigram_budget.max(2 * longest)
+        } else {
+            trigram_budget
+        };
+
+        'rounds: for round in 0..rounds {
+            if budgets.iter().all(|&b| b == 0) {
+                break;
+            }
+            for (i, units) in units.iter().enumerate() {
+                let budget = &mut budgets[if per_word { i } else { 0 }];
+                if *budget == 0 {
+                    continue;
+                }
+                match hooks.stop() {
+                    Some(true) => break 'rounds,
+                    Some(false) => return None,
+                    None => {}
+                }
+                // The first round always runs, so there is something to rank
+                if round > 0 && hooks.out_of_time() {
+                    break 'rounds;
                 }
 
-                let bytes = word.as_bytes();
-                let Some(pos) = trigram_position(bytes.len(), round) else {
+                let pos = if rarest.is_empty() {
+                    trigram_position(units.len(), round)
+                } else {
+                    rarest[i].get(round).copied()
+                };
+                let Some(pos) = pos else {
                     continue;
                 };
-                let trigram = [
-                    bytes[pos] as char,
-                    bytes[pos + 1] as char,
-                    bytes[pos + 2] as char,
-                ];
+                let trigram = gram(units[pos], units[pos + 1], units[pos + 2]);
 
                 if !visited.insert(trigram) {
                     continue;
                 }
-                budget -= 1;
+                *budget -= 1;
 
-                let Some(items) = self.trigram_index.get(&trigram) else {
-                    continue;
-                };
-                hit_count += 1;
+                // The skip-gram at the same spot rides along for free.
+                let skip_gram = (use_skip_grams && pos + 3 < units.len())
+                    .then(|| gram(units[pos], units[pos + 1], units[pos + 3]))
+                    .filter(|gram| visited_skip_grams.insert(*gram));
+                let probes = [
+                    (
+                        grams
+                            .filter
+                            .may_contain(trigram)
+                            .then(|| grams.index.get(&trigram))
+                            .flatten(),
+                        true,
+                    ),
+                    (
+                        use_skip_grams
+                            .then(|| grams.skip_grams.get(&trigram))
+                            .flatten(),
+                        false,
+                    ),
+                    (
+                        skip_gram
+                            .filter(|&gram| grams.filter.may_contain(gram))
+                            .and_then(|gram| grams.index.get(&gram)),
+                        false,
+                    ),
+                ];
 
-                if has_pool {
-                    for &item in items {
-                        if let Some(score) = scores.get_mut(&item) {
-                            *score += 1;
+                for (items, positional) in probes {
+                    let Some(items) = items else {
+                        continue;
+                    };
+                    hit_count += unit;
+
+                    // Full credit when the trigram sits within one char of
+                    // where the indexed word has it, half credit otherwise.
+                    let credit = |item: *const str| {
+                        if unit == 1 || !positional {
+                            return unit;
                         }
-                    }
-                } else {
-                    for &item in items {
-                        if unsafe { &*item }.len() >= min_len {
-                            *scores.entry(item).or_default() += 1;
+                        match grams.positions.get(&(trigram, item)) {
+                            Some(&at) if (at as usize).abs_diff(pos) <= 1 => 2,
+                            _ => 1,
+                        }
+                    };
+
+                    if has_pool {
+                        for &item in items {
+                            if let Some(score) = scores.get_mut(&item) {
+                                *score += credit(item);
+                            }
+                        }
+                    } else {
+                        for &item in items {
+                            if unsafe { &*item }.len() < min_len {
+                                continue;
+                            }
+                            let full = scores.len() >= max_candidates;
+                            match scores.get_mut(&item) {
+                                Some(score) => *score += credit(item),
+                                None if !full => {
+                                    scores.insert(item, credit(item));
+                                }
+                                None => hooks.truncated.set(true),
+                            }
                         }
                     }
                 }
             }
         }
 
-        (scores, hit_count)
+        if let Some(metrics) = config.metrics() {
+            metrics.trigrams(visited.len() + visited_skip_grams.len());
+        }
+        hooks.end_phase(Phase::Trigrams);
+        Some(hit_count)
+    }
+}
+
+/// Builds a matcher with the default config, borrowing each item's text.
+impl<'a, T: AsRef<str> + ?Sized + 'a> FromIterator<&'a T> for QuickMatch<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        Self::from_items(iter)
+    }
+}
+
+/// Indexes more items under the matcher's own config.
+impl<'a, T: AsRef<str> + ?Sized + 'a, S: BuildHasher + Default + Clone> Extend<&'a T>
+    for QuickMatch<'a, S>
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        let sep = sep_table(&self.config);
+        for item in iter {
+            self.index(item.as_ref(), None, &sep);
+        }
+    }
+}
+
+/// Adds each posting of `from` to the same key in `into`, skipping `dropped`
+/// items. Returns how many postings were new.
+fn merge_postings<K: Hash + Eq, S: BuildHasher + Default>(
+    into: &mut HashMap<K, Postings<S>, S>,
+    from: HashMap<K, Postings<S>, S>,
+    dropped: &FxHashSet<*const str>,
+) -> usize {
+    let mut added = 0;
+    for (key, mut items) in from {
+        items.retain(|item| !dropped.contains(item));
+        if items.is_empty() {
+            continue;
+        }
+        let set = into.entry(key).or_default();
+        for item in items {
+            if set.insert(item) {
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+/// Share of the grams in either set that are in both.
+fn jaccard(a: &FxHashSet<Gram>, b: &FxHashSet<Gram>) -> f32 {
+    let shared = a.intersection(b).count();
+    let all = a.len() + b.len() - shared;
+    if all == 0 {
+        0.0
+    } else {
+        shared as f32 / all as f32
+    }
+}
+
+/// Cosine similarity of two term-weight vectors, 0 when either is empty.
+fn cosine(a: &FxHashMap<u64, f32>, b: &FxHashMap<u64, f32>) -> f32 {
+    let dot: f32 = a
+        .iter()
+        .filter_map(|(term, x)| b.get(term).map(|y| x * y))
+        .sum();
+    let norm = |v: &FxHashMap<u64, f32>| v.values().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+fn shrink_postings<K: Hash + Eq, S: BuildHasher>(map: &mut HashMap<K, Postings<S>, S>) {
+    for postings in map.values_mut() {
+        postings.shrink_to_fit();
+    }
+    map.shrink_to_fit();
+}
+
+/// Removes `dropped` items from every posting of `map`, and keys left
+/// without any. Returns how many postings were removed.
+fn prune_postings<K, S: BuildHasher>(
+    map: &mut HashMap<K, Postings<S>, S>,
+    dropped: &FxHashSet<*const str>,
+) -> usize {
+    let mut removed = 0;
+    map.retain(|_, items| {
+        let before = items.len();
+        items.retain(|item| !dropped.contains(item));
+        removed += before - items.len();
+        !items.is_empty()
+    });
+    removed
+}
+
+/// Trims and lowercases the query, dropping non-ASCII chars (except CJK
+/// ones with `cjk`) under ASCII case folding, and keeping them folded
+/// otherwise. With `nfkc`, compatibility chars are folded first, then
+/// apostrophes and hyphens are handled per `joiners`, emoji and symbols per
+/// `symbols`, and with `units` numbers joined to their units. A
+/// normalizer in the config replaces all of this.
+fn normalize(query: &str, config: &QuickMatchConfig, cjk: bool) -> String {
+    if let Some(normalizer) = config.normalizer() {
+        return normalizer(query).into_owned();
+    }
+    let query = if config.nfkc() {
+        nfkc::fold(query)
+    } else {
+        Cow::Borrowed(query)
+    };
+    let query = match config.joiners() {
+        Some(joiners) => then(query, |query| joiners::apply(query, joiners)),
+        None => query,
+    };
+    let query = then(query, |query| symbols::apply(query, config.symbols()));
+    let query = then(query, |query| split_marks(query, config));
+    let query = if config.units() {
+        then(query, units::join)
+    } else {
+        query
+    };
+    let folding = config.case_folding();
+    if folding != CaseFolding::Ascii {
+        return casefold::fold(query.trim(), folding).into_owned();
+    }
+    // Grapheme grams are looked up for any word, so other text stays too
+    let keep_all = config.grapheme_grams();
+    query
+        .trim()
+        .chars()
+        .filter(|&c| keep_all || c.is_ascii() || (cjk && cjk::is_cjk(c)))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// The text with the non-ASCII chars that `split_when` or
+/// `unicode_separators` split on turned into spaces; the ASCII ones are
+/// in the separator table already.
+fn split_marks<'t>(text: &'t str, config: &QuickMatchConfig) -> Cow<'t, str> {
+    let split = |c: char| !c.is_ascii() && config.splits(c);
+    if !config.splits_more() || text.is_ascii() || !text.chars().any(split) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.chars()
+            .map(|c| if split(c) { ' ' } else { c })
+            .collect(),
+    )
+}
+
+/// Applies a cleanup step that borrows unchanged text to text that may
+/// already be owned.
+fn then<'t>(text: Cow<'t, str>, step: impl FnOnce(&str) -> Cow<'_, str>) -> Cow<'t, str> {
+    match text {
+        Cow::Borrowed(text) => step(text),
+        Cow::Owned(text) => match step(&text) {
+            Cow::Borrowed(_) => Cow::Owned(text),
+            Cow::Owned(changed) => Cow::Owned(changed),
+        },
+    }
+}
+
+/// The distinct words of a normalized query, in order. With `cjk`, words
+/// holding CJK text are split into their index terms, and with
+/// `split_digits`, words are split between letters and digits.
+fn split_query<'q>(
+    query: &'q str,
+    sep: &'q [bool; 256],
+    cjk: bool,
+    config: &QuickMatchConfig,
+) -> Vec<&'q str> {
+    let mut query_words: Vec<&str> = vec![];
+    for w in words(query, sep) {
+        let split;
+        let terms = if cjk && !w.is_ascii() {
+            split = cjk::query_terms(w);
+            &split[..]
+        } else {
+            slice::from_ref(&w)
+        };
+        for &term in terms {
+            let mut push = |run| {
+                if !query_words.contains(&run) {
+                    query_words.push(run);
+                }
+            };
+            if config.split_digits() {
+                digit_runs(term).for_each(&mut push);
+            } else {
+                push(term);
+            }
+        }
+    }
+    query_words
+}
+
+/// What grams are windowed over: the word's chars, or with `graphemes` one
+/// char per grapheme cluster.
+#[cfg(feature = "unicode")]
+fn gram_units(word: &str, config: &QuickMatchConfig) -> Vec<char> {
+    if config.graphemes() {
+        return unicode::grapheme_units(word);
     }
+    word.chars().collect()
+}
+
+#[cfg(not(feature = "unicode"))]
+fn gram_units(word: &str, _config: &QuickMatchConfig) -> Vec<char> {
+    word.chars().collect()
 }
 
 /// Builds a byte lookup table from the configured separator chars. Separators
 /// are ASCII, so a byte-indexed table is exact even for multi-byte UTF-8:
 /// continuation and lead bytes are all >= 128 and never flagged.
-fn sep_table(separators: &[char]) -> [bool; 256] {
+fn sep_table(config: &QuickMatchConfig) -> [bool; 256] {
+    let path_separators: &[char] = if config.paths() {
+        &['/', '\\', '.']
+    } else {
+        &[]
+    };
+    let address_separators: &[char] = if config.web_addresses() {
+        &['@', '.', '/', ':', '?', '=', '&', '#', '+']
+    } else {
+        &[]
+    };
     let mut table = [false; 256];
-    for &c in separators {
+    let extra = path_separators.iter().chain(address_separators);
+    for &c in config.separators().iter().chain(extra) {
         if (c as usize) < 256 {
             table[c as usize] = true;
         }
     }
+    if config.splits_more() {
+        for b in 0..128u8 {
+            table[b as usize] |= config.splits(b as char);
+        }
+        // What non-ASCII chars it accepts become
+        table[b' ' as usize] = true;
+    }
+    // Kept joiners are what is left of them after normalizing, all inside
+    // words
+    if config.joiners() == Some(Joiners::Keep) {
+        table[b'\'' as usize] = false;
+        table[b'-' as usize] = false;
+    }
     table
 }
 
@@ -328,6 +3103,21 @@ fn words<'s>(text: &'s str, sep: &'s [bool; 256]) -> impl Iterator<Item = &'s st
     })
 }
 
+/// Splits `word` into runs of ASCII digits and runs of anything else.
+fn digit_runs(word: &str) -> impl Iterator<Item = &str> {
+    let mut rest = word;
+    iter::from_fn(move || {
+        let digit = rest.as_bytes().first()?.is_ascii_digit();
+        let end = rest
+            .bytes()
+            .position(|b| b.is_ascii_digit() != digit)
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        rest = tail;
+        Some(run)
+    })
+}
+
 /// Aligns the query words against the item's words, in order:
 /// - `matched`: query words matched as an in-order subsequence of item words
 /// - `position`: index of the item word where that run starts (or the item's
@@ -352,7 +3142,7 @@ fn word_match(item: &str, query_words: &[&str], sep: &[bool; 256]) -> (usize, us
 /// probes outward from the two ends toward the middle. Returns `None` when the
 /// round offers no fresh position.
 fn trigram_position(len: usize, round: usize) -> Option<usize> {
-    let max = len - 3;
+    let max = len.checked_sub(3)?;
     if round == 0 {
         return Some(0);
     }
@@ -0,0 +1,34 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Holder for a matcher that keeps serving queries while its replacement is
+/// built elsewhere, then swaps it in atomically.
+///
+/// Readers take a [`snapshot`](Self::snapshot), a cheap `Arc` clone that
+/// stays valid (and unchanged) after a swap until dropped. Any matcher
+/// works, typically a [`SharedQuickMatch`](crate::SharedQuickMatch), which
+/// can be cloned and extended to build the next version.
+#[derive(Debug, Default)]
+pub struct LiveIndex<T> {
+    current: RwLock<Arc<T>>,
+}
+
+impl<T> LiveIndex<T> {
+    pub fn new(index: T) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(index)),
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<T> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Publishes `index` to later snapshots and returns the previous one.
+    pub fn swap(&self, index: T) -> Arc<T> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *current, Arc::new(index))
+    }
+}
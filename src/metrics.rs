@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+/// Stages of a query, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Normalizing the query and splitting it into words.
+    Normalize,
+    /// Looking up known words and intersecting their postings.
+    Intersect,
+    /// Probing trigrams of the unknown words.
+    Trigrams,
+    /// Ordering candidates and cutting them to the limit.
+    Rank,
+}
+
+/// A query that took at least the config's slow-query threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowQuery<'q> {
+    /// The query after normalization.
+    pub query: &'q str,
+    pub elapsed: Duration,
+    /// How long each phase that ran took, in order.
+    pub phases: Vec<(Phase, Duration)>,
+}
+
+/// Callback for queries at or over the slow-query threshold.
+pub(crate) type OnSlowQuery = dyn Fn(&SlowQuery) + Send + Sync;
+
+/// Counters a caller can forward to Prometheus, statsd and the like, set
+/// with `QuickMatchConfig::with_metrics`. Every method does nothing unless
+/// overridden, and without metrics set nothing is counted at all.
+pub trait Metrics: Send + Sync {
+    /// A query was run.
+    fn query(&self) {}
+
+    /// `count` candidates were considered while ranking.
+    fn candidates(&self, _count: usize) {}
+
+    /// `count` trigrams (and skip-grams) were probed for a query's unknown
+    /// words.
+    fn trigrams(&self, _count: usize) {}
+
+    /// A query was answered from a precomputed cache.
+    fn cache_hit(&self) {}
+}
@@ -0,0 +1,34 @@
+/// Hashes per signature, split into `BANDS` bands of `ROWS`.
+const HASHES: usize = BANDS * ROWS;
+const BANDS: usize = 16;
+const ROWS: usize = 4;
+
+/// MinHash signature of a gram set: for each of `HASHES` seeded hashes, the
+/// smallest over the set. Two sets agree on a given entry with probability
+/// equal to their Jaccard similarity.
+pub(crate) fn signature(grams: impl Iterator<Item = u64>) -> [u64; HASHES] {
+    let mut signature = [u64::MAX; HASHES];
+    for gram in grams {
+        for (seed, min) in signature.iter_mut().enumerate() {
+            *min = (*min).min(mix(gram ^ (seed as u64).wrapping_mul(0x9e3779b97f4a7c15)));
+        }
+    }
+    signature
+}
+
+/// One key per band of the signature. Sets sharing a key are candidates:
+/// at similarity `s` that happens for some band with probability
+/// `1 - (1 - s^4)^16`, about 0.5 at `s = 0.5` and 0.99 at `s = 0.75`.
+pub(crate) fn band_keys(signature: &[u64; HASHES]) -> impl Iterator<Item = (usize, u64)> + '_ {
+    signature
+        .chunks(ROWS)
+        .enumerate()
+        .map(|(band, rows)| (band, rows.iter().fold(band as u64, |h, &row| mix(h ^ row))))
+}
+
+/// SplitMix64's finalizer.
+fn mix(mut h: u64) -> u64 {
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+    h ^ (h >> 31)
+}
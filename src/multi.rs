@@ -0,0 +1,53 @@
+use crate::QuickMatch;
+
+/// Several [`QuickMatch`] sources (e.g. products, categories, brands)
+/// queried together, their results merged into one ranked list.
+pub struct MultiMatch<'m, 'a> {
+    sources: Vec<(&'m QuickMatch<'a>, usize)>,
+    limit: usize,
+}
+
+/// A merged result, with the index of the source it came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourcedMatch<'a> {
+    pub source: usize,
+    pub item: &'a str,
+    pub score: f32,
+}
+
+impl<'m, 'a> MultiMatch<'m, 'a> {
+    /// Merges up to `limit` results across all sources.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            sources: vec![],
+            limit: limit.max(1),
+        }
+    }
+
+    /// Adds a source, queried under its own config but capped at `limit`
+    /// results. Sources are numbered in the order they are added.
+    pub fn with_source(mut self, qm: &'m QuickMatch<'a>, limit: usize) -> Self {
+        self.sources.push((qm, limit.max(1)));
+        self
+    }
+
+    /// Results of every source, by score (see `Match::score`). Equal scores
+    /// keep each source's own order, earlier sources first.
+    pub fn matches(&self, query: &str) -> Vec<SourcedMatch<'a>> {
+        let mut results: Vec<SourcedMatch<'a>> = vec![];
+        for (source, &(qm, limit)) in self.sources.iter().enumerate() {
+            let config = qm.config().clone().with_limit(limit);
+            results.extend(qm.matches_scored_with(query, &config).into_iter().map(|m| {
+                SourcedMatch {
+                    source,
+                    item: m.item,
+                    score: m.score,
+                }
+            }));
+        }
+        // Stable, so ties keep source and rank order
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(self.limit);
+        results
+    }
+}
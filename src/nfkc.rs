@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+
+/// The text with compatibility chars replaced by their plain equivalents,
+/// borrowed when there are none.
+///
+/// Covers the NFKC mappings that show up in typed and pasted text rather
+/// than the full tables: full-width ASCII ("１５" → "15") and the
+/// ideographic space, Latin ligatures ("ﬁ" → "fi"), super- and subscript
+/// digits, and the compatibility forms of a few punctuation marks.
+pub(crate) fn fold(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() || text.chars().all(|c| matches!(expand(c), Fold::Keep)) {
+        return Cow::Borrowed(text);
+    }
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match expand(c) {
+            Fold::Keep => folded.push(c),
+            Fold::Char(c) => folded.push(c),
+            Fold::Str(s) => folded.push_str(s),
+        }
+    }
+    Cow::Owned(folded)
+}
+
+enum Fold {
+    Keep,
+    Char(char),
+    Str(&'static str),
+}
+
+fn expand(c: char) -> Fold {
+    let u = c as u32;
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => Fold::Char(char::from_u32(u - 0xFEE0).unwrap()),
+        '\u{3000}' | '\u{00A0}' | '\u{2000}'..='\u{200A}' => Fold::Char(' '),
+        '\u{FB00}' => Fold::Str("ff"),
+        '\u{FB01}' => Fold::Str("fi"),
+        '\u{FB02}' => Fold::Str("fl"),
+        '\u{FB03}' => Fold::Str("ffi"),
+        '\u{FB04}' => Fold::Str("ffl"),
+        '\u{FB05}' | '\u{FB06}' => Fold::Str("st"),
+        '\u{2070}' => Fold::Char('0'),
+        '\u{00B9}' => Fold::Char('1'),
+        '\u{00B2}' => Fold::Char('2'),
+        '\u{00B3}' => Fold::Char('3'),
+        '\u{2074}'..='\u{2079}' => Fold::Char(char::from_u32(u - 0x2074 + '4' as u32).unwrap()),
+        '\u{2080}'..='\u{2089}' => Fold::Char(char::from_u32(u - 0x2080 + '0' as u32).unwrap()),
+        '\u{2024}' => Fold::Char('.'),
+        '\u{2025}' => Fold::Str(".."),
+        '\u{2026}' => Fold::Str("..."),
+        '\u{FE50}' => Fold::Char(','),
+        '\u{FE52}' => Fold::Char('.'),
+        '\u{FE55}' => Fold::Char(':'),
+        _ => Fold::Keep,
+    }
+}
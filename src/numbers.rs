@@ -0,0 +1,62 @@
+const CARDINALS: [&str; 21] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+    "twenty",
+];
+
+const ORDINALS: [(&str, &str); 20] = [
+    ("1st", "first"),
+    ("2nd", "second"),
+    ("3rd", "third"),
+    ("4th", "fourth"),
+    ("5th", "fifth"),
+    ("6th", "sixth"),
+    ("7th", "seventh"),
+    ("8th", "eighth"),
+    ("9th", "ninth"),
+    ("10th", "tenth"),
+    ("11th", "eleventh"),
+    ("12th", "twelfth"),
+    ("13th", "thirteenth"),
+    ("14th", "fourteenth"),
+    ("15th", "fifteenth"),
+    ("16th", "sixteenth"),
+    ("17th", "seventeenth"),
+    ("18th", "eighteenth"),
+    ("19th", "nineteenth"),
+    ("20th", "twentieth"),
+];
+
+/// The other spelling of a number from 0 to 20 or an ordinal from 1st to
+/// 20th: "5" ↔ "five", "3rd" ↔ "third".
+pub(crate) fn equivalent(word: &str) -> Option<String> {
+    if let Ok(n) = word.parse::<usize>() {
+        return CARDINALS.get(n).map(|w| w.to_string());
+    }
+    if let Some(n) = CARDINALS.iter().position(|&w| w == word) {
+        return Some(n.to_string());
+    }
+    ORDINALS.iter().find_map(|&(digits, spelled)| match word {
+        w if w == digits => Some(spelled.to_string()),
+        w if w == spelled => Some(digits.to_string()),
+        _ => None,
+    })
+}
@@ -0,0 +1,152 @@
+use std::{
+    hash::BuildHasher,
+    io::{self, BufRead},
+    path::Path,
+};
+
+use rustc_hash::{FxBuildHasher, FxHashMap};
+
+use crate::{QuickMatch, QuickMatchConfig, persist};
+
+/// [`QuickMatch`] over its own copy of the items, interned into one
+/// contiguous buffer.
+///
+/// Identical items are stored once. Nothing borrows from the caller, so
+/// there is no lifetime to carry around, and results read from one block
+/// of memory instead of scattered allocations.
+pub struct OwnedQuickMatch {
+    // Points into `arena`, which is never written again once built.
+    inner: QuickMatch<'static>,
+    arena: String,
+}
+
+impl OwnedQuickMatch {
+    /// Expect the items to be pre-formatted (lowercase)
+    pub fn new<S: AsRef<str>>(items: &[S]) -> Self {
+        Self::new_with(items, QuickMatchConfig::default())
+    }
+
+    /// Expect the items to be pre-formatted (lowercase)
+    pub fn new_with<S: AsRef<str>>(items: &[S], config: QuickMatchConfig) -> Self {
+        let mut arena = String::with_capacity(items.iter().map(|s| s.as_ref().len()).sum());
+        let mut spans: FxHashMap<&str, (usize, usize)> = FxHashMap::default();
+        let items: Vec<(usize, usize)> = items
+            .iter()
+            .map(|item| {
+                let item = item.as_ref();
+                *spans.entry(item).or_insert_with(|| {
+                    arena.push_str(item);
+                    (arena.len() - item.len(), arena.len())
+                })
+            })
+            .collect();
+
+        let mut inner = QuickMatch::new_with(&[], config);
+        // The spans are all laid out before the first borrow, so `arena`
+        // never reallocates under them.
+        inner.extend(
+            items
+                .into_iter()
+                .map(|(start, end)| unsafe { &*(&arena[start..end] as *const str) }),
+        );
+        Self { inner, arena }
+    }
+
+    /// Streams the lines of `reader` straight into the arena, without
+    /// their line breaks, so the corpus is never held twice. Blank lines
+    /// are skipped.
+    pub fn from_lines(reader: impl BufRead) -> io::Result<Self> {
+        Self::from_lines_with(reader, QuickMatchConfig::default())
+    }
+
+    pub fn from_lines_with(mut reader: impl BufRead, config: QuickMatchConfig) -> io::Result<Self> {
+        let mut arena = String::new();
+        // First span seen with each hash; a colliding item is just stored
+        // again
+        let mut spans: FxHashMap<u64, (usize, usize)> = FxHashMap::default();
+        let mut items = vec![];
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let item = line.trim_end_matches(['\n', '\r']);
+            if !item.trim().is_empty() {
+                let hash = FxBuildHasher.hash_one(item);
+                let span = match spans.get(&hash) {
+                    Some(&(start, end)) if arena[start..end] == *item => (start, end),
+                    seen => {
+                        arena.push_str(item);
+                        let span = (arena.len() - item.len(), arena.len());
+                        if seen.is_none() {
+                            spans.insert(hash, span);
+                        }
+                        span
+                    }
+                };
+                items.push(span);
+            }
+            line.clear();
+        }
+        arena.shrink_to_fit();
+
+        let mut inner = QuickMatch::new_with(&[], config);
+        inner.extend(
+            items
+                .into_iter()
+                .map(|(start, end)| unsafe { &*(&arena[start..end] as *const str) }),
+        );
+        Ok(Self { inner, arena })
+    }
+
+    /// The underlying matcher, for the full query API.
+    pub fn matcher(&self) -> &QuickMatch<'_> {
+        &self.inner
+    }
+
+    pub fn matches(&self, query: &str) -> Vec<&str> {
+        self.inner.matches(query)
+    }
+
+    pub fn matches_with(&self, query: &str, config: &QuickMatchConfig) -> Vec<&str> {
+        self.inner.matches_with(query, config)
+    }
+
+    /// Rebuilds the matcher's indexes over the interned items under
+    /// `config`.
+    pub fn reindex_with(&mut self, config: QuickMatchConfig) {
+        self.inner.reindex_with(config);
+    }
+
+    pub fn optimize(&mut self) {
+        self.inner.optimize();
+    }
+
+    /// Writes the items, one per line, for `load` (or `UpdateLog::load`)
+    /// to rebuild the matcher from, after a format version and the index
+    /// settings of the config and before a checksum. The file is replaced
+    /// only once fully written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save_items(path.as_ref(), self.inner.iter(), self.inner.config())
+    }
+
+    /// Rebuilds a saved matcher. Files of another format version, saved
+    /// under other index settings than `config`'s, or failing their
+    /// checksum are refused with an `InvalidData` error wrapping a
+    /// [`LoadError`](crate::LoadError).
+    pub fn load(path: impl AsRef<Path>, config: QuickMatchConfig) -> io::Result<Self> {
+        Ok(Self::new_with(
+            &persist::load_items(path.as_ref(), &config)?,
+            config,
+        ))
+    }
+
+    /// Bytes taken by the interned item text.
+    pub fn arena_len(&self) -> usize {
+        self.arena.len()
+    }
+}
+
+/// Interns the items of any iterator, e.g. a `Vec<String>` by value.
+impl<T: AsRef<str>> FromIterator<T> for OwnedQuickMatch {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{LoadError, OwnedQuickMatch, QuickMatchConfig};
+
+/// One change recorded in an [`UpdateLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    Insert(String),
+    /// Removes every item with this text.
+    Remove(String),
+}
+
+/// Append-only file of inserts and removals, replayed on top of a base
+/// saved with `OwnedQuickMatch::save`, so small changes don't rewrite the
+/// whole base. `compact` folds it into a new base.
+pub struct UpdateLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl UpdateLog {
+    /// Opens the log at `path` for appending, creating it if missing.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, file })
+    }
+
+    pub fn insert(&mut self, item: &str) -> io::Result<()> {
+        writeln!(self.file, "+{}", escape(item))
+    }
+
+    pub fn remove(&mut self, item: &str) -> io::Result<()> {
+        writeln!(self.file, "-{}", escape(item))
+    }
+
+    /// The recorded changes, oldest first.
+    pub fn updates(&self) -> io::Result<Vec<Update>> {
+        let mut updates = vec![];
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            let line = line?;
+            let update = match line.split_at_checked(1) {
+                Some(("+", item)) => Update::Insert(unescape(item)?),
+                Some(("-", item)) => Update::Remove(unescape(item)?),
+                _ => return Err(invalid("update log line must start with + or -")),
+            };
+            updates.push(update);
+        }
+        Ok(updates)
+    }
+
+    /// Builds a matcher from the base at `base` with the log replayed.
+    pub fn load(
+        &self,
+        base: impl AsRef<Path>,
+        config: QuickMatchConfig,
+    ) -> io::Result<OwnedQuickMatch> {
+        let items = self.replay(load_items(base.as_ref(), &config)?)?;
+        Ok(OwnedQuickMatch::new_with(&items, config))
+    }
+
+    /// Writes the base with the log replayed over `base` and empties the
+    /// log, returning the matcher built from it. The new base replaces the
+    /// old one only once fully written; a crash before the log is emptied
+    /// leaves its inserts to be applied twice.
+    pub fn compact(
+        &mut self,
+        base: impl AsRef<Path>,
+        config: QuickMatchConfig,
+    ) -> io::Result<OwnedQuickMatch> {
+        let base = base.as_ref();
+        let items = self.replay(load_items(base, &config)?)?;
+        save_items(base, items.iter().map(String::as_str), &config)?;
+        self.file.set_len(0)?;
+        Ok(OwnedQuickMatch::new_with(&items, config))
+    }
+
+    fn replay(&self, mut items: Vec<String>) -> io::Result<Vec<String>> {
+        for update in self.updates()? {
+            match update {
+                Update::Insert(item) => items.push(item),
+                Update::Remove(item) => items.retain(|i| *i != item),
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// First line of a saved index, followed by the format version.
+const MAGIC: &str = "quickmatch-index";
+const VERSION: u32 = 1;
+
+/// Writes the format version, the config's index settings, one escaped
+/// item per line and a checksum of the settings and items to a temporary
+/// file next to `path`, then moves it into place.
+pub(crate) fn save_items<'i>(
+    path: &Path,
+    items: impl Iterator<Item = &'i str>,
+    config: &QuickMatchConfig,
+) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    writeln!(out, "{MAGIC} {VERSION}")?;
+    let mut checksum = Checksum::default();
+    let settings = format!("config {}", config.index_settings());
+    checksum.line(&settings);
+    writeln!(out, "{settings}")?;
+    for item in items {
+        let line = escape(item);
+        checksum.line(&line);
+        writeln!(out, "{line}")?;
+    }
+    writeln!(out, "checksum {}", checksum.hex())?;
+    out.into_inner()?.sync_all()?;
+    fs::rename(tmp, path)
+}
+
+/// The items of a file written by `save_items`, refused with a
+/// [`LoadError`] when its version, index settings or checksum don't match.
+pub(crate) fn load_items(path: &Path, config: &QuickMatchConfig) -> io::Result<Vec<String>> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let version = header
+        .strip_prefix(MAGIC)
+        .and_then(|rest| rest.strip_prefix(' '))
+        .ok_or(LoadError::NotAnIndex)
+        .map_err(refused)?;
+    if version != VERSION.to_string() {
+        return Err(refused(LoadError::Version {
+            found: version.to_string(),
+            supported: VERSION,
+        }));
+    }
+    let mut lines = lines.collect::<io::Result<Vec<String>>>()?;
+    let saved = lines
+        .pop()
+        .and_then(|last| last.strip_prefix("checksum ").map(str::to_string))
+        .filter(|_| !lines.is_empty())
+        .ok_or_else(|| refused(LoadError::Truncated))?;
+    let mut checksum = Checksum::default();
+    lines.iter().for_each(|line| checksum.line(line));
+    if checksum.hex() != saved {
+        return Err(refused(LoadError::Checksum {
+            saved,
+            computed: checksum.hex(),
+        }));
+    }
+    let saved = lines[0].strip_prefix("config ").unwrap_or_default();
+    let given = config.index_settings();
+    if saved != given {
+        return Err(refused(LoadError::ConfigMismatch {
+            saved: saved.to_string(),
+            given,
+        }));
+    }
+    lines[1..].iter().map(|line| unescape(line)).collect()
+}
+
+/// 64-bit FNV-1a over lines, each ended by a line break.
+struct Checksum(u64);
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Checksum {
+    fn line(&mut self, line: &str) {
+        for &b in line.as_bytes().iter().chain(b"\n") {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// Backslash-escapes line breaks so each item takes one line.
+fn escape(item: &str) -> String {
+    item.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(line: &str) -> io::Result<String> {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            _ => return Err(invalid("bad escape in saved item")),
+        }
+    }
+    Ok(out)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn refused(error: LoadError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
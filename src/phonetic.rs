@@ -0,0 +1,32 @@
+/// Sound-alike key for a word: Soundex digits for every consonant, the first
+/// letter included and with no length cap, so "kathryn" and "catherine" both
+/// give "2365", and "jovanovich" matches "jovanovic".
+///
+/// Vowels split runs of the same digit, "h" and "w" don't, as in Soundex.
+/// Returns `None` when the word yields fewer than three digits: shorter
+/// keys ("chian" and "gain" both give "25") collide with far too many
+/// words to be useful.
+pub(crate) fn phonetic_key(word: &str) -> Option<String> {
+    let mut key = String::new();
+    let mut last = None;
+    for c in word.chars() {
+        let code = match c {
+            'b' | 'f' | 'p' | 'v' => '1',
+            'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => '2',
+            'd' | 't' => '3',
+            'l' => '4',
+            'm' | 'n' => '5',
+            'r' => '6',
+            'h' | 'w' => continue,
+            _ => {
+                last = None;
+                continue;
+            }
+        };
+        if last != Some(code) {
+            key.push(code);
+        }
+        last = Some(code);
+    }
+    (key.len() >= 3).then_some(key)
+}
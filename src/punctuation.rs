@@ -0,0 +1,34 @@
+/// Whether `c` is whitespace (including no-break and zero-width spaces) or
+/// punctuation, so it separates words.
+///
+/// Punctuation follows Unicode's P categories for ASCII and Latin-1, and
+/// covers the punctuation blocks rather than the full tables elsewhere:
+/// general and supplemental punctuation, CJK and full-width forms, and the
+/// marks of the Armenian, Hebrew, Arabic, Devanagari, Thai and Ethiopic
+/// scripts. Math, currency and other symbols don't separate.
+pub(crate) fn is_separator(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(c,
+            '!'..='#' | '%'..='*' | ','..='/' | ':' | ';' | '?' | '@' | '['..=']' | '_' | '{' | '}'
+            | '\u{A1}' | '\u{A7}' | '\u{AB}' | '\u{B6}' | '\u{B7}' | '\u{BB}' | '\u{BF}'
+            | '\u{55A}'..='\u{55F}' | '\u{589}' | '\u{58A}'
+            | '\u{5BE}' | '\u{5C0}' | '\u{5C3}' | '\u{5C6}' | '\u{5F3}' | '\u{5F4}'
+            | '\u{60C}' | '\u{60D}' | '\u{61B}' | '\u{61E}' | '\u{61F}' | '\u{66A}'..='\u{66D}'
+            | '\u{6D4}'
+            | '\u{964}' | '\u{965}' | '\u{970}'
+            | '\u{E4F}' | '\u{E5A}' | '\u{E5B}'
+            | '\u{1361}'..='\u{1368}'
+            // Zero-width space, which isn't whitespace to `char`
+            | '\u{200B}'
+            | '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{2043}' | '\u{2045}'..='\u{2051}'
+            | '\u{2053}'..='\u{205E}'
+            | '\u{2E00}'..='\u{2E4F}'
+            | '\u{3001}'..='\u{3003}' | '\u{3008}'..='\u{3011}' | '\u{3014}'..='\u{301F}'
+            | '\u{3030}' | '\u{303D}' | '\u{30FB}'
+            | '\u{FE10}'..='\u{FE19}' | '\u{FE30}'..='\u{FE4F}' | '\u{FE50}'..='\u{FE61}'
+            | '\u{FE63}' | '\u{FE68}' | '\u{FE6A}' | '\u{FE6B}'
+            | '\u{FF01}'..='\u{FF03}' | '\u{FF05}'..='\u{FF0A}' | '\u{FF0C}'..='\u{FF0F}'
+            | '\u{FF1A}' | '\u{FF1B}' | '\u{FF1F}' | '\u{FF20}' | '\u{FF3B}'..='\u{FF3D}'
+            | '\u{FF3F}' | '\u{FF5B}' | '\u{FF5D}' | '\u{FF5F}'..='\u{FF65}'
+        )
+}
@@ -0,0 +1,34 @@
+/// A query in the small search syntax: quoted phrases (`"series x"`) whose
+/// words must sit next to each other in an item, and words with a leading
+/// minus (`-refurbished`) that an item must not hold. Everything else is
+/// searched as usual. Run it with `QuickMatch::matches_parsed`; plain
+/// string queries don't go through this syntax.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedQuery {
+    pub words: Vec<String>,
+    pub phrases: Vec<String>,
+    pub excluded: Vec<String>,
+}
+
+impl ParsedQuery {
+    /// Splits `query` on whitespace outside quotes. An unclosed quote runs
+    /// to the end of the query, and a lone `-` is an ordinary word.
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        for (i, part) in query.split('"').enumerate() {
+            if i % 2 == 1 {
+                if !part.trim().is_empty() {
+                    parsed.phrases.push(part.trim().to_string());
+                }
+                continue;
+            }
+            for token in part.split_whitespace() {
+                match token.strip_prefix('-') {
+                    Some(word) if !word.is_empty() => parsed.excluded.push(word.to_string()),
+                    _ => parsed.words.push(token.to_string()),
+                }
+            }
+        }
+        parsed
+    }
+}
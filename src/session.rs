@@ -0,0 +1,63 @@
+use std::{cell::RefCell, hash::BuildHasher};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{Hooks, QuickMatch, Scratch, normalize};
+
+/// Queries typed one keystroke at a time. When a query extends the one
+/// before it ("appl" → "apple", "apple" → "apple pie"), its candidates are
+/// narrowed down from those of the previous query instead of intersected
+/// from the postings of every word.
+///
+/// Extending a query only narrows its word and prefix matches, so results
+/// are those of a fresh search; infix, phonetic and acronym matches, which
+/// may not narrow, can be missed until the query stops extending. With a
+/// capped `prefix_expansion`, a longer prefix isn't sure to match a
+/// subset of what a shorter one did, so every query is searched afresh.
+pub struct Session<'m, 'a, S = FxBuildHasher> {
+    matcher: &'m QuickMatch<'a, S>,
+    /// The last normalized query and its candidate pool.
+    last: Option<(String, Vec<*const str>)>,
+    scratch: Scratch,
+}
+
+impl<'m, 'a, S: BuildHasher + Default + Clone> Session<'m, 'a, S> {
+    pub(crate) fn new(matcher: &'m QuickMatch<'a, S>) -> Self {
+        Self {
+            matcher,
+            last: None,
+            scratch: Scratch::default(),
+        }
+    }
+
+    pub fn matches(&mut self, query: &str) -> Vec<&'a str> {
+        let config = self.matcher.config();
+        let normalized = normalize(query, config, self.matcher.cjk(config));
+        let within = self
+            .last
+            .take()
+            .filter(|_| config.prefix_expansion().is_none())
+            .filter(|(last, _)| normalized.starts_with(last.as_str()))
+            .map(|(_, pool)| pool);
+        let pool = RefCell::default();
+        let hooks = Hooks {
+            within: within.as_deref(),
+            keep_pool: Some(&pool),
+            ..Hooks::new(config)
+        };
+        let results = self
+            .matcher
+            .search(query, config, &hooks, &mut self.scratch)
+            .unwrap_or_default();
+        let pool = pool.into_inner();
+        if !pool.is_empty() {
+            self.last = Some((normalized, pool));
+        }
+        results.into_iter().map(|m| m.item).collect()
+    }
+
+    /// Forgets the previous query, e.g. when the input is cleared.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
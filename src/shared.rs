@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use crate::{QuickMatch, QuickMatchConfig};
+
+/// [`QuickMatch`] over `Arc<str>` items.
+///
+/// Shares ownership of the items with the caller instead of borrowing them,
+/// so there is no lifetime to carry around. Results are `Arc` clones.
+#[derive(Clone)]
+pub struct SharedQuickMatch {
+    // Points into the `items` allocations, which never move while held.
+    inner: QuickMatch<'static>,
+    items: Vec<Arc<str>>,
+}
+
+impl SharedQuickMatch {
+    /// Expect the items to be pre-formatted (lowercase)
+    pub fn new(items: &[Arc<str>]) -> Self {
+        Self::new_with(items, QuickMatchConfig::default())
+    }
+
+    /// Expect the items to be pre-formatted (lowercase)
+    pub fn new_with(items: &[Arc<str>], config: QuickMatchConfig) -> Self {
+        let mut qm = Self {
+            inner: QuickMatch::new_with(&[], config),
+            items: vec![],
+        };
+        qm.extend(items.iter().cloned());
+        qm
+    }
+
+    pub fn matches(&self, query: &str) -> Vec<Arc<str>> {
+        self.matches_with(query, self.inner.config())
+    }
+
+    pub fn matches_with(&self, query: &str, config: &QuickMatchConfig) -> Vec<Arc<str>> {
+        self.inner
+            .matches_with(query, config)
+            .into_iter()
+            .map(share)
+            .collect()
+    }
+}
+
+/// Builds a matcher with the default config.
+impl FromIterator<Arc<str>> for SharedQuickMatch {
+    fn from_iter<I: IntoIterator<Item = Arc<str>>>(iter: I) -> Self {
+        let mut qm = Self::new(&[]);
+        qm.extend(iter);
+        qm
+    }
+}
+
+/// Indexes more items under the matcher's own config.
+impl Extend<Arc<str>> for SharedQuickMatch {
+    fn extend<I: IntoIterator<Item = Arc<str>>>(&mut self, iter: I) {
+        let start = self.items.len();
+        self.items.extend(iter);
+        let added = &self.items[start..];
+        self.inner
+            .extend(added.iter().map(|item| unsafe { &*Arc::as_ptr(item) }));
+    }
+}
+
+/// Turns a result back into a handle on the `Arc` it was borrowed from.
+fn share(item: &str) -> Arc<str> {
+    // Every result points at the data of an `Arc<str>` kept alive in `items`.
+    unsafe {
+        Arc::increment_strong_count(item as *const str);
+        Arc::from_raw(item as *const str)
+    }
+}
@@ -0,0 +1,49 @@
+const MATCH: i32 = 16;
+const CONSECUTIVE: i32 = 8;
+const WORD_START: i32 = 8;
+const GAP_START: i32 = 3;
+const GAP_EXTENSION: i32 = 1;
+
+/// fzf-style score for `pattern` appearing in `item` as an in-order char
+/// subsequence, or `None` when it doesn't. Each matched char earns a base
+/// score, plus a bonus when it follows the previous match directly or
+/// starts a word; each gap costs a start penalty and a little per skipped
+/// char.
+///
+/// Every occurrence of the first char is tried as a start, extending
+/// greedily from there, and the best alignment wins.
+pub(crate) fn subsequence_score(item: &str, pattern: &[u8], sep: &[bool; 256]) -> Option<i32> {
+    let bytes = item.as_bytes();
+    let &first = pattern.first()?;
+
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == first)
+        .filter_map(|(start, _)| align(bytes, pattern, start, sep))
+        .max()
+}
+
+/// Greedy alignment of `pattern` in `bytes` with its first char at `start`.
+fn align(bytes: &[u8], pattern: &[u8], start: usize, sep: &[bool; 256]) -> Option<i32> {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    let mut at = start;
+
+    for &p in pattern {
+        let pos = at + bytes[at..].iter().position(|&b| b == p)?;
+        score += MATCH;
+        if pos == 0 || sep[bytes[pos - 1] as usize] {
+            score += WORD_START;
+        }
+        match prev {
+            Some(prev) if pos == prev + 1 => score += CONSECUTIVE,
+            Some(prev) => score -= GAP_START + GAP_EXTENSION * (pos - prev - 2) as i32,
+            None => {}
+        }
+        prev = Some(pos);
+        at = pos + 1;
+    }
+
+    Some(score)
+}
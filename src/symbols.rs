@@ -0,0 +1,55 @@
+use std::borrow::Cow;
+
+use crate::Symbols;
+
+const ZWJ: char = '\u{200D}';
+
+/// The text with its emoji and symbols dropped, or set off by spaces into
+/// words of their own, borrowed when it holds none.
+///
+/// A run of symbol chars, such as an emoji joined by zero-width joiners or
+/// carrying a skin tone, stays together as one word when isolated.
+pub(crate) fn apply(text: &str, symbols: Symbols) -> Cow<'_, str> {
+    if symbols == Symbols::Keep || !text.chars().any(is_symbol) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 2);
+    let mut in_run = false;
+    for c in text.chars() {
+        let symbol = is_symbol(c);
+        match symbols {
+            Symbols::Strip if symbol => {
+                // Keep the words on either side of a dropped symbol apart
+                if !in_run && !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            Symbols::Isolate if symbol != in_run => {
+                out.push(' ');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+        in_run = symbol;
+    }
+    Cow::Owned(out)
+}
+
+/// Whether `c` is an emoji, a pictograph, or a dash, arrow, shape or other
+/// symbol that isn't part of a word. Covers the Unicode blocks these come
+/// from rather than the full emoji tables.
+fn is_symbol(c: char) -> bool {
+    matches!(c,
+        '\u{00A9}' | '\u{00AE}' | '\u{2122}'
+        // Dashes, bullets and ellipsis
+        | '\u{2010}'..='\u{2015}' | '\u{2022}' | '\u{2026}'
+        // Arrows, math operators, technical symbols, box drawing, shapes,
+        // dingbats and the miscellaneous symbols and arrows
+        | '\u{2190}'..='\u{23FF}' | '\u{2500}'..='\u{27BF}' | '\u{2900}'..='\u{2BFF}'
+        // Variation selectors
+        | '\u{FE00}'..='\u{FE0F}'
+        // Mahjong tiles through the emoji blocks, and flag tags
+        | '\u{1F000}'..='\u{1FAFF}' | '\u{E0020}'..='\u{E007F}'
+        | ZWJ
+    )
+}
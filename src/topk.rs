@@ -0,0 +1,68 @@
+use std::cmp::Ordering;
+
+/// The best `k` of the items pushed under `cmp` (`Less` is better), kept
+/// in a binary heap with the worst of them on top, so each push costs
+/// O(log k) and memory stays at `k` whatever the candidate count.
+pub(crate) struct TopK<T, F> {
+    heap: Vec<T>,
+    k: usize,
+    cmp: F,
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> TopK<T, F> {
+    pub(crate) fn new(k: usize, cmp: F) -> Self {
+        Self {
+            heap: Vec::with_capacity(k),
+            k,
+            cmp,
+        }
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        if self.heap.len() < self.k {
+            self.heap.push(item);
+            self.sift_up(self.heap.len() - 1);
+        } else if self.k > 0 && (self.cmp)(&item, &self.heap[0]) == Ordering::Less {
+            self.heap[0] = item;
+            self.sift_down(0);
+        }
+    }
+
+    /// The kept items, best first.
+    pub(crate) fn into_sorted_vec(self) -> Vec<T> {
+        let mut items = self.heap;
+        items.sort_unstable_by(&self.cmp);
+        items
+    }
+
+    fn worse(&self, a: usize, b: usize) -> bool {
+        (self.cmp)(&self.heap[a], &self.heap[b]) == Ordering::Greater
+    }
+
+    fn sift_up(&mut self, mut at: usize) {
+        while at > 0 {
+            let parent = (at - 1) / 2;
+            if !self.worse(at, parent) {
+                break;
+            }
+            self.heap.swap(at, parent);
+            at = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut at: usize) {
+        loop {
+            let mut worst = at;
+            for child in [2 * at + 1, 2 * at + 2] {
+                if child < self.heap.len() && self.worse(child, worst) {
+                    worst = child;
+                }
+            }
+            if worst == at {
+                break;
+            }
+            self.heap.swap(at, worst);
+            at = worst;
+        }
+    }
+}
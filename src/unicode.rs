@@ -0,0 +1,91 @@
+use std::hash::BuildHasher;
+
+use rustc_hash::FxBuildHasher;
+
+const ZWJ: char = '\u{200D}';
+
+/// The word as one char per grapheme cluster, for windowing grams: a lone
+/// char stands for itself, a longer cluster for a private-use char derived
+/// from its text, so "é" written as "e" plus a combining accent, or a
+/// family emoji, fills a single slot of a trigram.
+///
+/// Clusters follow the common cases of Unicode's extended grapheme rules
+/// rather than the full tables: combining marks (Latin, Hebrew, Arabic,
+/// Indic and Thai ranges), variation selectors, emoji skin tones and tags
+/// extend the char before them, a zero-width joiner glues on the next char,
+/// and regional indicators pair up into flags.
+pub(crate) fn grapheme_units(word: &str) -> Vec<char> {
+    let mut units = vec![];
+    let mut cluster = String::new();
+    let mut glue = false;
+    let mut flag_half = false;
+
+    for c in word.chars() {
+        let joins = !cluster.is_empty() && (glue || extends(c) || (flag_half && is_regional(c)));
+        if !joins && !cluster.is_empty() {
+            units.push(unit(&cluster));
+            cluster.clear();
+        }
+        // A lone regional indicator waits for the other half of its flag
+        flag_half = !joins && is_regional(c);
+        glue = c == ZWJ;
+        cluster.push(c);
+    }
+    if !cluster.is_empty() {
+        units.push(unit(&cluster));
+    }
+    units
+}
+
+fn unit(cluster: &str) -> char {
+    let mut chars = cluster.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        // Supplementary Private Use Area-A: U+F0000..=U+FFFFD
+        _ => char::from_u32(0xF0000 + (FxBuildHasher.hash_one(cluster) % 0xFFFE) as u32).unwrap(),
+    }
+}
+
+fn is_regional(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+/// Whether `c` attaches to the cluster before it.
+fn extends(c: char) -> bool {
+    let u = c as u32;
+    // Dependent signs of the Indic blocks (Devanagari through Sinhala),
+    // which share one layout within each 128-char block.
+    if (0x0900..=0x0DFF).contains(&u) {
+        let at = u & 0x7F;
+        return matches!(at, 0x00..=0x03 | 0x3A..=0x3C | 0x3E..=0x4F | 0x51..=0x57 | 0x62..=0x63);
+    }
+    matches!(
+        c,
+        ZWJ | '\u{200C}'
+            | '\u{0300}'..='\u{036F}'
+            | '\u{0483}'..='\u{0489}'
+            | '\u{0591}'..='\u{05BD}'
+            | '\u{05BF}'
+            | '\u{05C1}'..='\u{05C2}'
+            | '\u{05C4}'..='\u{05C5}'
+            | '\u{05C7}'
+            | '\u{0610}'..='\u{061A}'
+            | '\u{064B}'..='\u{065F}'
+            | '\u{0670}'
+            | '\u{06D6}'..='\u{06DC}'
+            | '\u{06DF}'..='\u{06E4}'
+            | '\u{06E7}'..='\u{06E8}'
+            | '\u{06EA}'..='\u{06ED}'
+            | '\u{0E31}'
+            | '\u{0E34}'..='\u{0E3A}'
+            | '\u{0E47}'..='\u{0E4E}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0020}'..='\u{E007F}'
+            | '\u{E0100}'..='\u{E01EF}'
+    )
+}
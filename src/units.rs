@@ -0,0 +1,59 @@
+use std::borrow::Cow;
+
+/// Units written after a number, longest first so "gbps" wins over "gb".
+const UNITS: &[&str] = &[
+    "gbps", "mbps", "ghz", "mhz", "mah", "fps", "lbs", "gb", "mb", "tb", "kb", "hz", "mp", "kw",
+    "mm", "cm", "km", "kg", "mg", "lb", "oz", "ml", "hp", "ft", "k", "p", "w", "v", "m", "g", "l",
+];
+
+/// The text with each number starting a word joined to the unit after it,
+/// lowercased, so "16GB", "16 gb" and "16 Gb" all read "16gb". A number is
+/// digits with an optional decimal part, and a unit must end its word.
+/// Borrowed when nothing changes.
+pub(crate) fn join(text: &str) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts_word = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+        if !starts_word || !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let mut end = i + digits(&bytes[i..]);
+        if bytes.get(end) == Some(&b'.') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit) {
+            end += 1 + digits(&bytes[end + 1..]);
+        }
+        let spaced = end + bytes[end..].iter().take_while(|&&b| b == b' ').count();
+        let Some(unit) = unit_at(&bytes[spaced..]) else {
+            i = end;
+            continue;
+        };
+        let after = spaced + unit.len();
+        if spaced > end || !text[spaced..after].bytes().all(|b| b.is_ascii_lowercase()) {
+            out.push_str(&text[copied..end]);
+            out.push_str(unit);
+            copied = after;
+        }
+        i = after;
+    }
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    out.push_str(&text[copied..]);
+    Cow::Owned(out)
+}
+
+fn digits(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// The unit `bytes` starts with, in any case, when it ends the word.
+fn unit_at(bytes: &[u8]) -> Option<&'static str> {
+    UNITS.iter().copied().find(|unit| {
+        bytes.len() >= unit.len()
+            && bytes[..unit.len()].eq_ignore_ascii_case(unit.as_bytes())
+            && !bytes.get(unit.len()).is_some_and(u8::is_ascii_alphanumeric)
+    })
+}
@@ -0,0 +1,10 @@
+use quickmatch::QuickMatch;
+
+#[test]
+fn completes_a_prefix_that_is_already_a_word() {
+    let qm = QuickMatch::new(&["apple pie", "apple", "applesauce", "apricot jam"]);
+    assert_eq!(qm.complete("apple"), ["apple", "applesauce"]);
+    assert_eq!(qm.complete("ap"), ["apple", "applesauce", "apricot"]);
+    assert_eq!(qm.complete("jam"), ["jam"]);
+    assert!(qm.complete("kiwi").is_empty());
+}
@@ -0,0 +1,30 @@
+use quickmatch::{QuickMatch, QuickMatchConfig};
+
+const ITEMS: [&str; 3] = ["crème brûlée", "प्रधानमंत्री कार्यालय", "apple pie"];
+
+fn graphemes(on: bool) -> QuickMatchConfig {
+    QuickMatchConfig::default().with_graphemes(on)
+}
+
+#[test]
+fn non_ascii_typos_match_over_graphemes() {
+    let qm = QuickMatch::new_with(&ITEMS, graphemes(true));
+    assert_eq!(qm.matches("crèmme"), ["crème brûlée"]);
+    assert_eq!(qm.matches("प्रधानमत्री"), ["प्रधानमंत्री कार्यालय"]);
+}
+
+#[test]
+fn non_ascii_typos_need_graphemes() {
+    let qm = QuickMatch::new_with(&ITEMS, graphemes(false));
+    assert!(qm.matches("crèmme").is_empty());
+    assert!(qm.matches("प्रधानमत्री").is_empty());
+}
+
+#[test]
+fn ascii_queries_are_unchanged() {
+    let on = QuickMatch::new_with(&ITEMS, graphemes(true));
+    let off = QuickMatch::new_with(&ITEMS, graphemes(false));
+    for query in ["aple", "apple pei", "pie"] {
+        assert_eq!(on.matches(query), off.matches(query));
+    }
+}
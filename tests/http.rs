@@ -0,0 +1,85 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::OnceLock,
+    thread,
+};
+
+use quickmatch::{QuickMatch, QuickMatchConfig, http};
+
+const ITEMS: [&str; 4] = ["apple pie", "apple", "banana \"split\"", "cherry tart"];
+
+/// One server for all tests, on a free local port.
+fn server() -> SocketAddr {
+    static ADDR: OnceLock<SocketAddr> = OnceLock::new();
+    *ADDR.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let qm: &'static QuickMatch<'static> = Box::leak(Box::new(QuickMatch::new_with(
+            &ITEMS,
+            QuickMatchConfig::default().with_limit(3),
+        )));
+        thread::spawn(move || http::serve(qm, listener));
+        addr
+    })
+}
+
+/// The status line and body answering `request`.
+fn send(request: &[u8]) -> (String, String) {
+    let mut stream = TcpStream::connect(server()).unwrap();
+    stream.write_all(request).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+fn get(target: &str) -> (String, String) {
+    send(format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+}
+
+#[test]
+fn searches() {
+    let (status, body) = get("/search?q=apple&limit=1");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(body, "[{\"item\":\"apple\",\"score\":1}]");
+}
+
+#[test]
+fn decodes_queries_and_escapes_items() {
+    let (_, plus) = get("/search?q=apple+pie");
+    let (_, percent) = get("/search?q=apple%20pie");
+    assert_eq!(plus, percent);
+    assert!(plus.starts_with("[{\"item\":\"apple pie\""));
+    let (_, body) = get("/search?q=banana");
+    assert_eq!(body, "[{\"item\":\"banana \\\"split\\\"\",\"score\":1}]");
+}
+
+#[test]
+fn caps_limit_at_the_matchers() {
+    let (_, body) = get("/search?q=a&limit=50");
+    assert!(body.matches("\"item\"").count() <= 3);
+}
+
+#[test]
+fn rejects_bad_requests() {
+    assert_eq!(get("/search?q=apple&limit=x").0, "HTTP/1.1 400 Bad Request");
+    assert_eq!(get("/other").0, "HTTP/1.1 404 Not Found");
+    let post = send(b"POST /search HTTP/1.1\r\n\r\n");
+    assert_eq!(post.0, "HTTP/1.1 405 Method Not Allowed");
+}
+
+#[test]
+fn refuses_oversized_requests() {
+    let long_target = format!("/search?q={}", "a".repeat(9000));
+    assert_eq!(get(&long_target).0, "HTTP/1.1 414 URI Too Long");
+
+    let headers: String = (0..400)
+        .map(|i| format!("X-Filler-{i}: {}\r\n", "b".repeat(40)))
+        .collect();
+    let request = format!("GET /search?q=apple HTTP/1.1\r\n{headers}\r\n");
+    assert_eq!(
+        send(request.as_bytes()).0,
+        "HTTP/1.1 431 Request Header Fields Too Large"
+    );
+}
@@ -0,0 +1,130 @@
+use std::{fs, io, path::PathBuf};
+
+use quickmatch::ingest::{Ingest, Ingested};
+
+/// Writes `contents` to a fresh file in the temp dir, unique to the test.
+fn file(name: &str, contents: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("quickmatch-ingest-{}-{name}", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn items(ingested: &Ingested) -> Vec<&str> {
+    ingested.matcher().matcher().iter().collect()
+}
+
+#[test]
+fn reads_quoted_csv_fields() {
+    let csv = "id, name ,note\r\n\
+               1,\"Cable, USB-C\",plain\r\n\
+               2,\"The \"\"Best\"\" Mouse\",\"note, with comma\"\r\n\
+               3,\"Two\r\nLine Stand\",\"multi\nline\"\r\n\
+               4,  Desk Lamp  ,\r\n\
+               5,,no name\r\n\
+               6\r\n\
+               7,cable\"s end,\r\n\
+               8,\"cable, usb-c\",again\r\n";
+    let ingested = Ingest::csv(file("quoted.csv", csv))
+        .key_column("name")
+        .payload_column("id")
+        .build()
+        .unwrap();
+    assert_eq!(
+        items(&ingested),
+        [
+            "cable, usb-c",
+            "the \"best\" mouse",
+            "two\r\nline stand",
+            "desk lamp",
+            "cable\"s end",
+            "cable, usb-c",
+        ]
+    );
+    // Rows sharing a key keep every payload, in file order
+    assert_eq!(ingested.payloads("cable, usb-c"), ["1", "8"]);
+    assert_eq!(ingested.payloads("the \"best\" mouse"), ["2"]);
+    assert_eq!(ingested.payloads("desk lamp"), ["4"]);
+    assert_eq!(
+        ingested.matches("mouse"),
+        [("the \"best\" mouse", &["2".to_string()][..])]
+    );
+}
+
+#[test]
+fn refuses_a_missing_csv_column() {
+    let path = file("columns.csv", "id,name\n1,lamp\n");
+    let error = Ingest::csv(&path)
+        .key_column("title")
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let error = Ingest::csv(&path)
+        .key_column("name")
+        .payload_column("sku")
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn reads_escaped_jsonl_fields() {
+    let jsonl = concat!(
+        r#"{"name": "Say \"Hi\" Mug", "id": 1}"#,
+        "\n",
+        r#"{"id": "b-2", "name": "back\\slash éclair"}"#,
+        "\n",
+        "\n",
+        r#"{"tags": {"name": "nested", "list": [1, {"x": "]"}]}, "name": "Tab\tand 🍎", "id": null}"#,
+        "\n",
+        r#"{"name": null, "id": 4}"#,
+        "\n",
+        r#"{"name": ["array"], "id": 5}"#,
+        "\n",
+        r#"{"id": 6}"#,
+        "\n",
+        r#"{"name": "  Line\nBreak  ", "id": true}"#,
+        "\n",
+        r#"{}"#,
+        "\n",
+    );
+    let ingested = Ingest::jsonl(file("escaped.jsonl", jsonl))
+        .key_column("name")
+        .payload_column("id")
+        .build()
+        .unwrap();
+    assert_eq!(
+        items(&ingested),
+        [
+            "say \"hi\" mug",
+            "back\\slash éclair",
+            "tab\tand 🍎",
+            "line\nbreak",
+        ]
+    );
+    assert_eq!(ingested.payloads("say \"hi\" mug"), ["1"]);
+    assert_eq!(ingested.payloads("back\\slash éclair"), ["b-2"]);
+    // A null payload reads as empty
+    assert_eq!(ingested.payloads("tab\tand 🍎"), [""]);
+    assert_eq!(ingested.payloads("line\nbreak"), ["true"]);
+}
+
+#[test]
+fn refuses_lines_that_are_not_json_objects() {
+    for line in [
+        r#"["name", "lamp"]"#,
+        r#"{"name": "lamp""#,
+        r#"{"name": "lamp" "id": 1}"#,
+        r#"{"name": "bad \ud83c escape"}"#,
+    ] {
+        let path = file("invalid.jsonl", &format!("{{\"name\": \"ok\"}}\n{line}\n"));
+        let error = Ingest::jsonl(&path).key_column("name").build().err();
+        assert_eq!(
+            error.map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData),
+            "{line}"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use quickmatch::{CancelToken, QuickMatch};
+
+const ITEMS: [&str; 5] = [
+    "apple pie",
+    "apple crumble with cream",
+    "banana split",
+    "cherry and apple tart",
+    "banana bread",
+];
+
+/// Polls `future` to the end, returning its output and how many polls
+/// handed control back first.
+fn run<T>(future: impl Future<Output = T>) -> (T, usize) {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut pending = 0;
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return (output, pending),
+            Poll::Pending => pending += 1,
+        }
+    }
+}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn yields_between_words_and_matches_the_sync_search() {
+    let qm = QuickMatch::new(&ITEMS);
+    let cancel = CancelToken::new();
+    for query in ["apple", "apple tart", "banana brad", "apple crumble cream"] {
+        let future = qm.matches_async(query, &cancel);
+        assert_send(&future);
+        let (results, pending) = run(future);
+        assert_eq!(results, Some(qm.matches(query)), "{query}");
+        // Once before each word and once before ranking
+        let words = query.split(' ').count();
+        assert_eq!(pending, words + 1, "{query}");
+    }
+}
+
+#[test]
+fn stops_between_words_once_cancelled() {
+    let qm = QuickMatch::new(&ITEMS);
+    let cancel = CancelToken::new();
+    let mut future = pin!(qm.matches_async("apple crumble cream", &cancel));
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    cancel.cancel();
+    assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(None));
+}
+
+#[test]
+fn rejected_queries_finish_without_yielding() {
+    let qm = QuickMatch::new(&ITEMS);
+    let cancel = CancelToken::new();
+    assert_eq!(run(qm.matches_async("", &cancel)), (Some(vec![]), 0));
+}
@@ -0,0 +1,70 @@
+use quickmatch::{QuickMatch, QuickMatchConfig};
+
+const QUERIES: [&str; 8] = [
+    "pe",
+    "pea",
+    "pear",
+    "peach pie",
+    "tart",
+    "bred",
+    "split",
+    "cake",
+];
+
+fn config() -> QuickMatchConfig {
+    QuickMatchConfig::default()
+        .with_dedup(true)
+        .with_bk_tree(true)
+}
+
+/// Asserts `qm` answers like a matcher freshly built from `items`.
+fn assert_fresh(qm: &QuickMatch, items: &[&str]) {
+    let fresh = QuickMatch::new_with(items, config());
+    assert_eq!(
+        qm.iter().collect::<Vec<_>>(),
+        fresh.iter().collect::<Vec<_>>()
+    );
+    for query in QUERIES {
+        assert_eq!(
+            qm.matches_scored(query),
+            fresh.matches_scored(query),
+            "{query}"
+        );
+        assert_eq!(qm.complete(query), fresh.complete(query), "{query}");
+        assert_eq!(qm.postings_len(query), fresh.postings_len(query), "{query}");
+    }
+    assert_eq!(
+        qm.nearest_words("pesch", 2),
+        fresh.nearest_words("pesch", 2)
+    );
+}
+
+#[test]
+fn merge_matches_a_fresh_build() {
+    let left = ["peach pie", "peach tart", "pear split"];
+    let right = ["pear split", "pear bread"];
+    let merged =
+        QuickMatch::new_with(&left, config()).merge(QuickMatch::new_with(&right, config()));
+    assert_eq!(merged.duplicates(), 1);
+    // The repeated "pear split" counts once, leaving "pear" tied with
+    // "peach" rather than the more common completion
+    assert_eq!(merged.complete("pe"), ["peach", "pear"]);
+    assert_fresh(
+        &merged,
+        &["peach pie", "peach tart", "pear split", "pear bread"],
+    );
+}
+
+#[test]
+fn retain_matches_a_fresh_build() {
+    let items = [
+        "peach pie",
+        "peach tart",
+        "pear split",
+        "pear bread",
+        "peach cake",
+    ];
+    let mut qm = QuickMatch::new_with(&items, config());
+    qm.retain(|item| !item.starts_with("peach"));
+    assert_fresh(&qm, &["pear split", "pear bread"]);
+}
@@ -0,0 +1,23 @@
+use quickmatch::{ParsedQuery, QuickMatch};
+
+const ITEMS: [&str; 4] = ["red apple pie", "apple red", "green apple", "pie crust"];
+
+#[test]
+fn empty_phrases_are_ignored() {
+    let qm = QuickMatch::new(&ITEMS);
+    for query in [r#"apple "--""#, r#"apple """#, r#"apple " ""#] {
+        let parsed = ParsedQuery::parse(query);
+        assert_eq!(
+            qm.matches_parsed(&parsed),
+            qm.matches_parsed(&ParsedQuery::parse("apple")),
+            "{query}"
+        );
+    }
+}
+
+#[test]
+fn phrases_still_need_their_words_in_a_row() {
+    let qm = QuickMatch::new(&ITEMS);
+    let parsed = ParsedQuery::parse(r#""red apple" "--""#);
+    assert_eq!(qm.matches_parsed(&parsed), ["red apple pie"]);
+}
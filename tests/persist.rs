@@ -0,0 +1,135 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use quickmatch::{LoadError, OwnedQuickMatch, QuickMatchConfig, Update, UpdateLog};
+
+const ITEMS: [&str; 4] = ["apple pie", "banana split", "two\nlines", "back\\slash"];
+
+/// A fresh path in the temp dir, unique to the test.
+fn path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("quickmatch-persist-{}-{name}", std::process::id()));
+    fs::remove_file(&path).ok();
+    path
+}
+
+fn items(qm: &OwnedQuickMatch) -> Vec<&str> {
+    qm.matcher().iter().collect()
+}
+
+/// The `LoadError` a load was refused with.
+fn refusal(result: io::Result<OwnedQuickMatch>) -> LoadError {
+    let error = result.err().expect("load should be refused");
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<LoadError>())
+        .expect("wraps a LoadError")
+        .clone()
+}
+
+fn saved(name: &str) -> PathBuf {
+    let path = path(name);
+    OwnedQuickMatch::new(&ITEMS).save(&path).unwrap();
+    path
+}
+
+fn edit(path: &Path, edit: impl FnOnce(String) -> String) {
+    let text = fs::read_to_string(path).unwrap();
+    fs::write(path, edit(text)).unwrap();
+}
+
+#[test]
+fn round_trips_items_with_line_breaks_and_backslashes() {
+    let path = saved("round-trip");
+    let loaded = OwnedQuickMatch::load(&path, QuickMatchConfig::default()).unwrap();
+    assert_eq!(items(&loaded), ITEMS);
+    assert_eq!(loaded.matches("lines"), ["two\nlines"]);
+    // Settings that only affect queries don't need to match
+    let config = QuickMatchConfig::default().with_limit(1);
+    assert!(OwnedQuickMatch::load(&path, config).is_ok());
+}
+
+#[test]
+fn refuses_edited_items() {
+    let path = saved("edited");
+    edit(&path, |text| text.replace("banana", "bandana"));
+    assert!(matches!(
+        refusal(OwnedQuickMatch::load(&path, QuickMatchConfig::default())),
+        LoadError::Checksum { .. }
+    ));
+}
+
+#[test]
+fn refuses_a_file_cut_short() {
+    let path = saved("truncated");
+    edit(&path, |text| {
+        let end = text.trim_end().rfind('\n').unwrap();
+        text[..=end].to_string()
+    });
+    assert_eq!(
+        refusal(OwnedQuickMatch::load(&path, QuickMatchConfig::default())),
+        LoadError::Truncated
+    );
+}
+
+#[test]
+fn refuses_other_index_settings() {
+    let path = saved("settings");
+    let config = QuickMatchConfig::default().with_dedup(true);
+    let LoadError::ConfigMismatch { saved, given } = refusal(OwnedQuickMatch::load(&path, config))
+    else {
+        panic!("expected a settings mismatch");
+    };
+    assert!(saved.contains("dedup=false"), "{saved}");
+    assert!(given.contains("dedup=true"), "{given}");
+}
+
+#[test]
+fn refuses_other_files_and_versions() {
+    let path = path("not-an-index");
+    fs::write(&path, "apple pie\nbanana split\n").unwrap();
+    assert_eq!(
+        refusal(OwnedQuickMatch::load(&path, QuickMatchConfig::default())),
+        LoadError::NotAnIndex
+    );
+
+    let path = saved("version");
+    edit(&path, |text| {
+        text.replacen("quickmatch-index 1", "quickmatch-index 9", 1)
+    });
+    assert_eq!(
+        refusal(OwnedQuickMatch::load(&path, QuickMatchConfig::default())),
+        LoadError::Version {
+            found: "9".to_string(),
+            supported: 1
+        }
+    );
+}
+
+#[test]
+fn replays_and_compacts_the_update_log() {
+    let base = saved("base");
+    let log_path = path("log");
+    let mut log = UpdateLog::open(&log_path).unwrap();
+    log.insert("cherry\ntart").unwrap();
+    log.remove("apple pie").unwrap();
+    assert_eq!(
+        log.updates().unwrap(),
+        [
+            Update::Insert("cherry\ntart".to_string()),
+            Update::Remove("apple pie".to_string()),
+        ]
+    );
+
+    let expected = ["banana split", "two\nlines", "back\\slash", "cherry\ntart"];
+    let loaded = log.load(&base, QuickMatchConfig::default()).unwrap();
+    assert_eq!(items(&loaded), expected);
+    let compacted = log.compact(&base, QuickMatchConfig::default()).unwrap();
+    assert_eq!(items(&compacted), expected);
+    assert!(log.updates().unwrap().is_empty());
+    let reloaded = OwnedQuickMatch::load(&base, QuickMatchConfig::default()).unwrap();
+    assert_eq!(items(&reloaded), expected);
+}
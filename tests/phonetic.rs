@@ -0,0 +1,40 @@
+use quickmatch::{QuickMatch, QuickMatchConfig};
+
+const ITEMS: [&str; 6] = [
+    "catherine zeta",
+    "realized_cap",
+    "realized_gain",
+    "realized_price",
+    "unrealized_loss",
+    "unrealized_profit",
+];
+
+fn phonetic(on: bool) -> QuickMatchConfig {
+    QuickMatchConfig::default().with_phonetic(on)
+}
+
+#[test]
+fn sound_alike_words_match_when_typos_find_nothing() {
+    let qm = QuickMatch::new_with(&ITEMS, phonetic(true));
+    assert_eq!(qm.matches("kathryn"), ["catherine zeta"]);
+}
+
+#[test]
+fn phonetic_needs_the_config_flag() {
+    let qm = QuickMatch::new_with(&ITEMS, phonetic(false));
+    assert!(qm.matches("kathryn").is_empty());
+}
+
+#[test]
+fn typo_matches_come_before_sound_alikes() {
+    let on = QuickMatch::new_with(&ITEMS, phonetic(true));
+    let off = QuickMatch::new_with(&ITEMS, phonetic(false));
+    assert_eq!(on.matches("realizd"), off.matches("realizd"));
+    assert!(on.matches("realizd").contains(&"unrealized_loss"));
+}
+
+#[test]
+fn short_keys_do_not_match() {
+    let qm = QuickMatch::new_with(&ITEMS, phonetic(true));
+    assert!(!qm.matches("chian").contains(&"realized_gain"));
+}
@@ -0,0 +1,34 @@
+use quickmatch::{QueryError, QuickMatch, QuickMatchConfig};
+
+const ITEMS: [&str; 3] = ["apple pie", "banana split", "cherry tart"];
+
+#[test]
+fn an_explicit_length_cap_keeps_the_word_cap() {
+    let qm = QuickMatch::new(&ITEMS);
+    let config = QuickMatchConfig::default().with_max_query_len(1000);
+    let long = "apple pie with cream and a cherry on top";
+    assert_eq!(
+        qm.try_matches_with(long, &config),
+        Err(QueryError::TooManyWords { count: 9, max: 4 })
+    );
+    assert!(qm.matches_with(long, &config).is_empty());
+    assert!(qm.matches(long).is_empty());
+}
+
+#[test]
+fn an_explicit_length_cap_still_applies() {
+    let qm = QuickMatch::new(&ITEMS);
+    let config = QuickMatchConfig::default().with_max_query_len(5);
+    assert_eq!(
+        qm.try_matches_with("apple pie", &config),
+        Err(QueryError::TooLong { len: 9, max: 5 })
+    );
+    assert_eq!(qm.matches_with("apple", &config), ["apple pie"]);
+    assert_eq!(
+        qm.matches_with(
+            "apple pie",
+            &QuickMatchConfig::default().with_max_query_len(1000)
+        ),
+        ["apple pie"]
+    );
+}
@@ -0,0 +1,53 @@
+use quickmatch::{QuickMatch, QuickMatchConfig};
+
+const ITEMS: [&str; 8] = [
+    "apple pie",
+    "apple crumble",
+    "applesauce",
+    "pineapple tart",
+    "apricot jam",
+    "banana split",
+    "banana bread",
+    "blueberry pie",
+];
+
+/// Every prefix of `text`, as typed.
+fn keystrokes(text: &str) -> impl Iterator<Item = &str> {
+    text.char_indices().map(|(i, c)| &text[..i + c.len_utf8()])
+}
+
+fn assert_narrows_like_fresh(config: QuickMatchConfig) {
+    let qm = QuickMatch::new_with(&ITEMS, config);
+    let mut session = qm.session();
+    let typed = keystrokes("apple pie")
+        // Backspacing, retyping and a typo
+        .chain(["apple p", "apple", "apple c", "apple crm", "apple cru"])
+        .chain(keystrokes("banana bread"))
+        .chain(["banana br", "blue", "blueberry pie"]);
+    for query in typed {
+        assert_eq!(session.matches(query), qm.matches(query), "{query}");
+    }
+    session.reset();
+    assert_eq!(session.matches("apple"), qm.matches("apple"));
+}
+
+#[test]
+fn narrowing_matches_a_fresh_search() {
+    assert_narrows_like_fresh(QuickMatchConfig::default());
+    assert_narrows_like_fresh(QuickMatchConfig::default().with_limit(2));
+}
+
+#[test]
+fn capped_prefix_expansion_searches_afresh() {
+    assert_narrows_like_fresh(QuickMatchConfig::default().with_prefix_expansion(1));
+
+    // "ap" expands to "apple" alone, whose items hold only one of the
+    // "apricot" items "apr" finds
+    let items = ["apple pie", "apricot apple", "apricot jam", "apricot tart"];
+    let qm = QuickMatch::new_with(&items, QuickMatchConfig::default().with_prefix_expansion(1));
+    let mut session = qm.session();
+    for query in keystrokes("apricot") {
+        assert_eq!(session.matches(query), qm.matches(query), "{query}");
+    }
+    assert_eq!(qm.matches("apr").len(), 3);
+}
@@ -0,0 +1,23 @@
+use quickmatch::{QuickMatch, QuickMatchConfig};
+
+fn config(skip_grams: bool) -> QuickMatchConfig {
+    QuickMatchConfig::default()
+        .with_trigram_budget(20)
+        .with_skip_grams(skip_grams)
+}
+
+#[test]
+fn adjacent_swaps_find_the_word() {
+    let items = ["abcdef", "their", "other", "zzzzzz"];
+    let qm = QuickMatch::new_with(&items, config(true));
+    assert!(qm.matches("abdcef").contains(&"abcdef"));
+    assert!(qm.matches("thier").contains(&"their"));
+}
+
+#[test]
+fn adjacent_swaps_need_skip_grams() {
+    let items = ["abcdef", "their", "other", "zzzzzz"];
+    let qm = QuickMatch::new_with(&items, config(false));
+    assert!(!qm.matches("abdcef").contains(&"abcdef"));
+    assert!(!qm.matches("thier").contains(&"their"));
+}
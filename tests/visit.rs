@@ -0,0 +1,61 @@
+use std::ops::ControlFlow;
+
+use quickmatch::{Match, QuickMatch, QuickMatchConfig};
+
+const ITEMS: [&str; 6] = [
+    "apple pie",
+    "apple",
+    "pineapple tart",
+    "apple crumble",
+    "banana split",
+    "apple juice",
+];
+
+fn visited<'a>(qm: &QuickMatch<'a>, query: &str) -> Vec<Match<'a>> {
+    let mut seen = vec![];
+    let flow: ControlFlow<()> = qm.matches_visit(query, |item, score| {
+        seen.push(Match { item, score });
+        ControlFlow::Continue(())
+    });
+    assert_eq!(flow, ControlFlow::Continue(()));
+    seen
+}
+
+#[test]
+fn visits_the_same_page_as_matches_scored() {
+    let configs = [
+        QuickMatchConfig::default(),
+        QuickMatchConfig::default().with_collapse(0.9),
+        QuickMatchConfig::default().with_input_order(true),
+        QuickMatchConfig::default().with_limit(2),
+    ];
+    for config in configs {
+        let qm = QuickMatch::new_with(&ITEMS, config);
+        for query in ["apple", "aple pie", "banana", "zzz"] {
+            assert_eq!(visited(&qm, query), qm.matches_scored(query));
+        }
+    }
+}
+
+#[test]
+fn visits_weighted_pages_in_order() {
+    let mut qm = QuickMatch::new(&ITEMS);
+    qm.set_weight("apple juice", 5.0);
+    assert_eq!(visited(&qm, "apple"), qm.matches_scored("apple"));
+}
+
+#[test]
+fn breaking_stops_the_visit() {
+    let qm = QuickMatch::new(&ITEMS);
+    let mut calls = 0;
+    let flow = qm.matches_visit("apple", |item, _| {
+        calls += 1;
+        if calls == 2 {
+            ControlFlow::Break(item)
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(calls, 2);
+    assert_eq!(flow, ControlFlow::Break(qm.matches("apple")[1]));
+}
@@ -0,0 +1,35 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use quickmatch::{Metrics, QuickMatch, QuickMatchConfig};
+
+#[derive(Default)]
+struct CacheHits(AtomicUsize);
+
+impl Metrics for CacheHits {
+    fn cache_hit(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn caches_the_prefixes_of_the_words_as_indexed() {
+    let hits = Arc::new(CacheHits::default());
+    let config = QuickMatchConfig::default()
+        .with_nfkc(true)
+        .with_metrics(hits.clone());
+    let items = ["ﬁsh tank", "ﬁre pit", "tent"];
+    let mut qm = QuickMatch::new_with(&items, config);
+    let cold: Vec<_> = ["f", "fi", "fis", "fir"]
+        .into_iter()
+        .map(|query| qm.matches(query))
+        .collect();
+    assert!(qm.warm_up(100) > 0);
+    for (query, cold) in ["f", "fi", "fis", "fir"].into_iter().zip(cold) {
+        let before = hits.0.load(Ordering::Relaxed);
+        assert_eq!(qm.matches(query), cold, "{query}");
+        assert_eq!(hits.0.load(Ordering::Relaxed), before + 1, "{query}");
+    }
+}